@@ -1,7 +1,5 @@
 #!/bin/bash
 
-cd /opt/jencli
+job_name=$(jencli list | jq -r .[].name | fzf)
 
-job_name=$(./jencli list | jq -r .[].name | fzf)
-
-./jencli build --name $job_name
\ No newline at end of file
+jencli build --name $job_name
\ No newline at end of file
@@ -1,77 +1,295 @@
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
+use chrono::Local;
 use log::debug;
 use serde::{Deserialize, Serialize};
 
+use crate::dirs::get_cache_dir;
 use crate::jenkins::list::JenkinsJob;
 
-const CACHE_DIR: &str = "cache";
-const CACHE_FILE: &str = "jobs.cache";
+const JOB_LIST_CACHE_TYPE: &str = "jobs";
+const CACHE_FILE_EXTENSION: &str = "cache";
 
+/// How cached calls use cache, set by `--refresh` and `--no-cache` flags.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CacheMode {
+    /// Cached data is used until it's stale
+    #[default]
+    Use,
+    /// Data is fetched and cache is rewritten
+    Refresh,
+    /// Data is fetched, cache isn't read or written
+    Bypass,
+}
+
+/// Job list with fetch time, unix seconds. Users might see different jobs,
+/// so cache is keyed by server url and username.
 #[derive(Serialize,Deserialize)]
 pub struct JobListCache {
+    pub jenkins_url: String,
+    pub username: String,
+    pub fetched_at: i64,
     pub jobs: Vec<JenkinsJob>
 }
 
-pub fn save_job_list_in_cache(jobs: &Vec<JenkinsJob>) -> anyhow::Result<()> {
+/// Cache file summary for `cache status` command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CacheStatus {
+    pub jenkins_url: String,
+    pub username: String,
+    /// `jobs` for job list
+    pub cache_type: String,
+    pub entries: usize,
+    /// Bytes
+    pub size: u64,
+    pub fetched_at: i64,
+}
+
+impl JobListCache {
+    pub fn is_stale(&self, ttl: Duration, now: i64) -> bool {
+        now.saturating_sub(self.fetched_at) >= ttl.as_secs() as i64
+    }
+
+    pub fn is_owned_by(&self, jenkins_url: &str, username: &str) -> bool {
+        get_server_key(&self.jenkins_url) == get_server_key(jenkins_url) && self.username == username
+    }
+}
+
+pub fn save_job_list_in_cache(jenkins_url: &str, username: &str, jobs: &[JenkinsJob]) -> anyhow::Result<()> {
+    save_job_list_in(&get_cache_dir(), jenkins_url, username, jobs)
+}
+
+fn save_job_list_in(cache_path: &Path, jenkins_url: &str, username: &str, jobs: &[JenkinsJob]) -> anyhow::Result<()> {
     debug!("save job list into cache: {:?}", jobs);
-    let cache_path = Path::new(CACHE_DIR);
 
     if !cache_path.exists() {
-        fs::create_dir(cache_path)?;
+        fs::create_dir_all(cache_path)?;
     }
 
-    let json = serde_json::to_string(&jobs)?;
+    let cache = JobListCache {
+        jenkins_url: jenkins_url.to_string(),
+        username: username.to_string(),
+        fetched_at: Local::now().timestamp(),
+        jobs: jobs.to_vec()
+    };
+
+    let json = serde_json::to_string(&cache)?;
 
-    let cache_file_path = cache_path.join(CACHE_FILE);
+    let cache_file_path = cache_path.join(get_cache_file_name(JOB_LIST_CACHE_TYPE, jenkins_url, username));
 
     fs::write(cache_file_path, json)?;
 
     Ok(())
 }
 
-pub fn load_job_list_from_cache() -> anyhow::Result<Vec<JenkinsJob>> {
-    let cache_file_path = Path::new(CACHE_DIR).join(CACHE_FILE);
+/// `None` if cache is missing, stale, has old format or belongs to another server or user.
+pub fn load_job_list_from_cache(jenkins_url: &str, username: &str,
+                                ttl: Duration) -> anyhow::Result<Option<Vec<JenkinsJob>>> {
+    load_job_list_in(&get_cache_dir(), jenkins_url, username, ttl)
+}
+
+fn load_job_list_in(cache_path: &Path, jenkins_url: &str, username: &str,
+                    ttl: Duration) -> anyhow::Result<Option<Vec<JenkinsJob>>> {
+    let cache_file_path = cache_path.join(get_cache_file_name(JOB_LIST_CACHE_TYPE, jenkins_url, username));
+
+    if !cache_file_path.exists() {
+        return Ok(None)
+    }
+
+    let json = fs::read_to_string(cache_file_path)?;
+
+    let cache = match serde_json::from_str::<JobListCache>(&json) {
+        Ok(cache) => cache,
+        Err(e) => {
+            debug!("unable to parse job list cache, ignored: {e}");
+            return Ok(None)
+        }
+    };
+
+    // File name might be the same for different keys, e.g. `dirk-gently` and `dirk_gently` users
+    if !cache.is_owned_by(jenkins_url, username) {
+        debug!("job list cache belongs to '{}' user '{}', ignored", cache.jenkins_url, cache.username);
+        return Ok(None)
+    }
+
+    if cache.is_stale(ttl, Local::now().timestamp()) {
+        debug!("job list cache is stale, fetched at {}", cache.fetched_at);
+        return Ok(None)
+    }
+
+    debug!("job list loaded from cache: {:?}", cache.jobs);
+
+    Ok(Some(cache.jobs))
+}
+
+/// Cache files which can't be parsed (e.g. old format) are skipped.
+pub fn get_cache_status() -> anyhow::Result<Vec<CacheStatus>> {
+    get_cache_status_in(&get_cache_dir())
+}
+
+fn get_cache_status_in(cache_path: &Path) -> anyhow::Result<Vec<CacheStatus>> {
+    let mut statuses: Vec<CacheStatus> = vec![];
+
+    for path in get_cache_files_in(cache_path)? {
+        let json = fs::read_to_string(&path)?;
 
-    if cache_file_path.exists() {
-        let json = fs::read_to_string(cache_file_path)?;
+        match serde_json::from_str::<JobListCache>(&json) {
+            Ok(cache) => statuses.push(CacheStatus {
+                jenkins_url: cache.jenkins_url,
+                username: cache.username,
+                cache_type: get_cache_type(&path),
+                entries: cache.jobs.len(),
+                size: json.len() as u64,
+                fetched_at: cache.fetched_at,
+            }),
+            Err(e) => debug!("unable to parse cache file '{}', skipped: {e}", path.display())
+        }
+    }
 
-        let jobs = serde_json::from_str(&json)?;
-        debug!("job list loaded from cache: {:?}", jobs);
+    Ok(statuses)
+}
+
+/// Returns count of removed cache files.
+pub fn clear_cache() -> anyhow::Result<usize> {
+    clear_cache_in(&get_cache_dir())
+}
 
-        Ok(jobs)
+fn clear_cache_in(cache_path: &Path) -> anyhow::Result<usize> {
+    let files = get_cache_files_in(cache_path)?;
 
-    } else {
-        Ok(vec![])
+    for path in &files {
+        debug!("remove cache file '{}'", path.display());
+        fs::remove_file(path)?;
     }
+
+    Ok(files.len())
+}
+
+fn get_cache_files_in(cache_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if !cache_path.exists() {
+        return Ok(vec![])
+    }
+
+    let mut files = fs::read_dir(cache_path)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+
+    files.retain(|path| path.is_file() &&
+        path.extension().is_some_and(|e| e == CACHE_FILE_EXTENSION));
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// `jobs-jenkins.company.com-dirk_gently.cache`, characters other than alphanumeric and `.` are replaced.
+fn get_cache_file_name(cache_type: &str, jenkins_url: &str, username: &str) -> String {
+    format!("{cache_type}-{}-{}.{CACHE_FILE_EXTENSION}", get_server_file_key(jenkins_url), sanitize(username))
+}
+
+/// Server part of file names, files of different servers never mix: `jenkins.company.com_8080`
+pub fn get_server_file_key(jenkins_url: &str) -> String {
+    sanitize(get_server_key(jenkins_url))
+}
+
+fn sanitize(value: &str) -> String {
+    value.chars()
+        .map(|c| match c.is_ascii_alphanumeric() || c == '.' {
+            true => c,
+            false => '_'
+        })
+        .collect()
+}
+
+/// `https://jenkins.company.com/` -> `jenkins.company.com`
+fn get_server_key(jenkins_url: &str) -> &str {
+    let url = jenkins_url.trim().trim_end_matches('/');
+
+    url.split_once("://").map(|(_, rest)| rest).unwrap_or(url)
+}
+
+/// `jobs-jenkins.company.com-dirk_gently.cache` -> `jobs`
+fn get_cache_type(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().split('-').next().unwrap_or_default().to_string())
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
 mod tests {
-    use std::fs;
-    use std::path::Path;
+    use std::{env, fs};
+    use std::time::Duration;
 
     use fake::{Fake, Faker};
 
-    use crate::cache::{CACHE_DIR, load_job_list_from_cache, save_job_list_in_cache};
+    use crate::cache::{clear_cache_in, get_cache_file_name, get_cache_status_in, JobListCache,
+                       load_job_list_in, save_job_list_in};
     use crate::jenkins::list::JenkinsJob;
 
+    const JENKINS_URL: &str = "https://jenkins.company.com";
+    const TTL: Duration = Duration::from_secs(60);
+
     #[test]
     fn cache_should_be_saved_and_loaded() {
-        cleanup();
+        // Cache is kept in temp directory instead of user cache directory
+        let cache_path = env::temp_dir().join("jencli-cache-tests");
+
+        if cache_path.exists() {
+            fs::remove_dir_all(&cache_path).unwrap();
+        }
 
         let job1 = get_sample_jenkins_job();
         let job2 = get_sample_jenkins_job();
 
-        save_job_list_in_cache(&vec![job1.clone(), job2.clone()]).unwrap();
+        save_job_list_in(&cache_path, JENKINS_URL, "dirk-gently", &[job1.clone(), job2.clone()]).unwrap();
 
-        let results = load_job_list_from_cache().unwrap();
+        let results = load_job_list_in(&cache_path, JENKINS_URL, "dirk-gently", TTL).unwrap().unwrap();
 
         assert_eq!(2, results.len());
 
         assert!(results.iter().find(|j|j.name == job1.name).is_some());
         assert!(results.iter().find(|j|j.name == job2.name).is_some());
+
+        let statuses = get_cache_status_in(&cache_path).unwrap();
+
+        assert_eq!(1, statuses.len());
+        assert_eq!(JENKINS_URL, statuses[0].jenkins_url);
+        assert_eq!("dirk-gently", statuses[0].username);
+        assert_eq!("jobs", statuses[0].cache_type);
+        assert_eq!(2, statuses[0].entries);
+
+        assert_eq!(1, clear_cache_in(&cache_path).unwrap());
+        assert!(load_job_list_in(&cache_path, JENKINS_URL, "dirk-gently", TTL).unwrap().is_none());
+    }
+
+    #[test]
+    fn cache_should_not_be_served_across_keys() {
+        let cache = JobListCache {
+            jenkins_url: format!("{JENKINS_URL}/"), username: "dirk-gently".to_string(), fetched_at: 1000, jobs: vec![]
+        };
+
+        assert!(cache.is_owned_by(JENKINS_URL, "dirk-gently"));
+        assert!(!cache.is_owned_by(JENKINS_URL, "dirk_gently"));
+        assert!(!cache.is_owned_by("https://jenkins-staging.company.com", "dirk-gently"));
+
+        assert_eq!("jobs-jenkins.company.com-dirk_gently.cache",
+                   get_cache_file_name("jobs", "https://jenkins.company.com/", "dirk-gently"));
+
+        assert_ne!(get_cache_file_name("jobs", JENKINS_URL, "dirk"),
+                   get_cache_file_name("jobs", "https://jenkins-staging.company.com", "dirk"));
+    }
+
+    #[test]
+    fn stale_cache_should_be_detected() {
+        let cache = JobListCache {
+            jenkins_url: JENKINS_URL.to_string(), username: "dirk-gently".to_string(), fetched_at: 1000, jobs: vec![]
+        };
+
+        assert!(!cache.is_stale(Duration::from_secs(3600), 1000 + 3599));
+        assert!(cache.is_stale(Duration::from_secs(3600), 1000 + 3600));
+        assert!(cache.is_stale(Duration::ZERO, 1000));
     }
 
     fn get_sample_jenkins_job() -> JenkinsJob {
@@ -81,14 +299,6 @@ mod tests {
         }
     }
 
-    fn cleanup() {
-        let cache_path = Path::new(CACHE_DIR);
-
-        if cache_path.exists() {
-            fs::remove_dir_all(&cache_path).unwrap();
-        }
-    }
-
     fn get_random_string() -> String {
         Faker.fake::<String>()
     }
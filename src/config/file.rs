@@ -1,10 +1,31 @@
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
 
 use anyhow::Context;
 use config::Config;
 use log::info;
 
 use crate::config::AppConfig;
+use crate::dirs::get_config_dir;
+
+const CONFIG_FILE: &str = "config.yml";
+
+const FAVORITES_KEY: &str = "favorites";
+
+/// Explicit path (`--config` or `JENCLI_CONFIG`) if present, then `$XDG_CONFIG_HOME/jencli/config.yml`.
+/// `config.yml` in working directory is still supported for installs like `/opt/jencli`.
+pub fn get_config_file_path(explicit_path: Option<&str>) -> PathBuf {
+    if let Some(path) = explicit_path {
+        return PathBuf::from(path)
+    }
+
+    let path = get_config_dir().join(CONFIG_FILE);
+
+    match !path.exists() && Path::new(CONFIG_FILE).exists() {
+        true => PathBuf::from(CONFIG_FILE),
+        false => path
+    }
+}
 
 pub fn load_config_from_file(file_path: &Path) -> anyhow::Result<AppConfig> {
     let file_path = format!("{}", file_path.display());
@@ -23,12 +44,79 @@ pub fn load_config_from_file(file_path: &Path) -> anyhow::Result<AppConfig> {
     Ok(config)
 }
 
+/// Only `favorites` block is rewritten, the rest of file (comments included) is kept as is.
+pub fn save_favorites_to_file(file_path: &Path, favorites: &[String]) -> anyhow::Result<()> {
+    info!("save favorites into config file '{}'", file_path.display());
+
+    let yaml = fs::read_to_string(file_path)
+        .context(format!("unable to read config file '{}'", file_path.display()))?;
+
+    fs::write(file_path, set_favorites_in_yaml(&yaml, favorites))
+        .context(format!("unable to write config file '{}'", file_path.display()))?;
+
+    Ok(())
+}
+
+fn set_favorites_in_yaml(yaml: &str, favorites: &[String]) -> String {
+    let mut block = match favorites.is_empty() {
+        true => vec![format!("{FAVORITES_KEY}: []")],
+        false => vec![format!("{FAVORITES_KEY}:")]
+    };
+
+    favorites.iter().for_each(|f| block.push(format!("  - '{}'", f.replace('\'', "''"))));
+
+    let mut result: Vec<String> = vec![];
+    let mut lines = yaml.lines().peekable();
+    let mut replaced = false;
+
+    while let Some(line) = lines.next() {
+        if replaced || !line.starts_with(&format!("{FAVORITES_KEY}:")) {
+            result.push(line.to_string());
+            continue
+        }
+
+        // Block lasts while lines are indented or blank, `- item` without indent is allowed by yaml too
+        let mut blank_lines = 0;
+
+        while let Some(next) = lines.peek() {
+            if next.trim().is_empty() {
+                blank_lines += 1;
+
+            } else if next.starts_with(' ') || next.starts_with('-') {
+                blank_lines = 0;
+
+            } else {
+                break
+            }
+
+            lines.next();
+        }
+
+        result.extend(block.clone());
+        result.extend(vec!["".to_string(); blank_lines]);
+        replaced = true;
+    }
+
+    if !replaced {
+        if result.last().is_some_and(|l| !l.trim().is_empty()) {
+            result.push("".to_string());
+        }
+
+        result.extend(block);
+    }
+
+    result.join("\n") + "\n"
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::path::Path;
+    use std::time::Duration;
 
-    use crate::config::{AppConfig, ListCommandConfig};
-    use crate::config::file::load_config_from_file;
+    use crate::cache::CacheMode;
+    use crate::config::{AppConfig, CacheConfig, ListCommandConfig, TriggerToken};
+    use crate::config::file::{load_config_from_file, set_favorites_in_yaml};
 
     #[test]
     fn config_should_be_loaded() {
@@ -38,16 +126,53 @@ mod tests {
 
         let expected_config = AppConfig {
             jenkins_url: "https://jenkins.company.com".to_string(),
-            username: "dirk-gently".to_string(),
-            token: "113451439abdecb02af1e7064387666458".to_string(),
+            username: Some("dirk-gently".to_string()),
+            token: Some("113451439abdecb02af1e7064387666458".to_string()),
 
             list: ListCommandConfig {
                 exclude: vec![
                     "PROD-".to_string(), "STAGING-".to_string(), "TEST-".to_string()
                 ],
+                default_view: Some("team/backend".to_string()),
             },
+
+            trigger_tokens: vec![
+                TriggerToken { job: "backend/api/deploy-dev".to_string(), token: "deploy-secret".to_string() }
+            ],
+
+            favorites: vec!["backend/api/deploy-dev".to_string()],
+
+            aliases: HashMap::from([("api-dev".to_string(), "backend/api/deploy-dev".to_string())]),
+
+            cache: CacheConfig { ttl: Duration::from_secs(30 * 60), mode: CacheMode::Use },
         };
 
         assert_eq!(config, expected_config);
+        assert!(config.has_credentials());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn credentials_might_be_omitted() {
+        let path = Path::new("test-data").join("token-only-config.yml");
+
+        let config = load_config_from_file(&path).unwrap();
+
+        assert!(!config.has_credentials());
+        assert_eq!("", config.get_username());
+        assert_eq!(Some("deploy-secret"), config.get_trigger_token("backend/api/deploy-dev"));
+        assert_eq!(CacheConfig::default(), config.cache);
+    }
+
+    #[test]
+    fn favorites_block_should_be_replaced() {
+        let yaml = "jenkins-url: 'x'\n\nfavorites:\n  - 'old'\n\n  - 'older'\n# comment\nlist:\n  exclude: []\n";
+
+        assert_eq!("jenkins-url: 'x'\n\nfavorites:\n  - 'backend/api'\n  - 'it''s'\n# comment\nlist:\n  exclude: []\n",
+                   set_favorites_in_yaml(yaml, &["backend/api".to_string(), "it's".to_string()]));
+
+        assert_eq!("list:\n  exclude: []\n\nfavorites:\n  - 'api'\n",
+                   set_favorites_in_yaml("list:\n  exclude: []", &["api".to_string()]));
+
+        assert_eq!("favorites: []\n\nlist: {}\n", set_favorites_in_yaml("favorites: ['api']\n\nlist: {}\n", &[]));
+    }
+}
@@ -1,34 +1,128 @@
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer};
+
+use crate::cache::CacheMode;
+use crate::jenkins::parse_duration;
 
 pub mod file;
 
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(3600);
+
 #[derive(Deserialize,PartialEq,Clone,Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct AppConfig {
     pub jenkins_url: String,
-    pub username: String,
-    pub token: String,
 
-    pub list: ListCommandConfig
+    /// Might be omitted if only jobs with trigger tokens are built, see `has_credentials`
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub token: Option<String>,
+
+    pub list: ListCommandConfig,
+
+    /// Jobs with "Trigger builds remotely" enabled, see `build_job`
+    #[serde(default)]
+    pub trigger_tokens: Vec<TriggerToken>,
+
+    /// Full job names, see `list --favorites`
+    #[serde(default)]
+    pub favorites: Vec<String>,
+
+    /// Short name -> full job name. Keys are lowercased by config loader.
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+
+    #[serde(default)]
+    pub cache: CacheConfig
+}
+
+#[derive(Deserialize,PartialEq,Clone,Debug)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct CacheConfig {
+    /// Job list is refetched once cache is older, `0` disables cache
+    #[serde(deserialize_with = "duration_from_str")]
+    pub ttl: Duration,
+
+    /// Set from command line flags
+    #[serde(skip)]
+    pub mode: CacheMode
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig { ttl: DEFAULT_CACHE_TTL, mode: CacheMode::Use }
+    }
+}
+
+/// `30s`, `15m`, `2h`, `7d`, see `parse_duration`
+fn duration_from_str<'de, D>(deserializer: D) -> Result<Duration, D::Error> where D: Deserializer<'de> {
+    let value = String::deserialize(deserializer)?;
+    parse_duration(&value).map_err(serde::de::Error::custom)
+}
+
+#[derive(Deserialize,PartialEq,Clone,Debug)]
+#[serde(rename_all = "kebab-case")]
+pub struct TriggerToken {
+    pub job: String,
+    pub token: String
 }
 
 #[derive(Deserialize,PartialEq,Clone,Debug)]
 #[serde(rename_all = "kebab-case")]
 pub struct ListCommandConfig {
-    pub exclude: Vec<String>
+    pub exclude: Vec<String>,
+
+    /// View used by `list` without `--view`, nested views are `parent/child`
+    #[serde(default)]
+    pub default_view: Option<String>
+}
+
+impl AppConfig {
+    /// Full job name for alias (case-insensitive), name itself otherwise.
+    pub fn resolve_alias(&self, name: &str) -> String {
+        self.aliases.get(&name.to_lowercase()).cloned().unwrap_or(name.to_string())
+    }
+
+    pub fn has_credentials(&self) -> bool {
+        self.username.is_some() && self.token.is_some()
+    }
+
+    /// Empty without credentials, commands other than `build` require them (see `load_config`).
+    pub fn get_username(&self) -> &str {
+        self.username.as_deref().unwrap_or_default()
+    }
+
+    pub fn get_token(&self) -> &str {
+        self.token.as_deref().unwrap_or_default()
+    }
+
+    pub fn get_trigger_token(&self, job_name: &str) -> Option<&str> {
+        find_trigger_token(&self.trigger_tokens, job_name)
+    }
+}
+
+pub fn find_trigger_token<'a>(trigger_tokens: &'a [TriggerToken], job_name: &str) -> Option<&'a str> {
+    trigger_tokens.iter()
+        .find(|t| t.job.trim_matches('/') == job_name.trim_matches('/'))
+        .map(|t| t.token.as_str())
 }
 
 impl Display for AppConfig {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[AppConfig] jenkins_url: '{}', username: '{}', token: '*******', list: {} [/AppConfig]",
-               self.jenkins_url, self.username, self.list)
+        write!(f, "[AppConfig] jenkins_url: '{}', username: {:?}, token: '*******', list: {}, trigger tokens: {:?}, \
+                   favorites: {:?}, aliases: {:?}, cache ttl: {:?} [/AppConfig]",
+               self.jenkins_url, self.username, self.list,
+               self.trigger_tokens.iter().map(|t| t.job.as_str()).collect::<Vec<&str>>(),
+               self.favorites, self.aliases, self.cache.ttl)
     }
 }
 
 impl Display for ListCommandConfig {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "exclude: {:?}", self.exclude)
+        write!(f, "exclude: {:?}, default view: {:?}", self.exclude, self.default_view)
     }
 }
\ No newline at end of file
@@ -0,0 +1,53 @@
+use std::env;
+use std::path::PathBuf;
+
+const APP_DIR: &str = "jencli";
+
+/// `$XDG_CONFIG_HOME/jencli`, `~/.config/jencli` by default
+pub fn get_config_dir() -> PathBuf {
+    get_app_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// `$XDG_CACHE_HOME/jencli`, `~/.cache/jencli` by default
+pub fn get_cache_dir() -> PathBuf {
+    get_app_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// Logs, build history and scheduled builds. `$XDG_STATE_HOME/jencli`, `~/.local/state/jencli` by default
+pub fn get_state_dir() -> PathBuf {
+    get_app_dir("XDG_STATE_HOME", ".local/state")
+}
+
+/// Working directory is used if neither xdg variable nor `HOME` is set.
+fn get_app_dir(xdg_variable: &str, default_home_dir: &str) -> PathBuf {
+    get_base_dir(env::var(xdg_variable).ok(), env::var("HOME").ok(), default_home_dir)
+        .map(|dir| dir.join(APP_DIR))
+        .unwrap_or(PathBuf::from("."))
+}
+
+/// Relative paths in xdg variables are ignored, as base directory specification requires.
+fn get_base_dir(xdg_value: Option<String>, home: Option<String>, default_home_dir: &str) -> Option<PathBuf> {
+    let xdg_dir = xdg_value.map(PathBuf::from).filter(|dir| dir.is_absolute());
+
+    xdg_dir.or(home.filter(|home| !home.is_empty()).map(|home| PathBuf::from(home).join(default_home_dir)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::dirs::get_base_dir;
+
+    #[test]
+    fn base_dir_should_be_resolved() {
+        assert_eq!(Some(PathBuf::from("/tmp/cache")),
+                   get_base_dir(Some("/tmp/cache".to_string()), Some("/home/dirk".to_string()), ".cache"));
+
+        assert_eq!(Some(PathBuf::from("/home/dirk/.local/state")),
+                   get_base_dir(Some("state".to_string()), Some("/home/dirk".to_string()), ".local/state"));
+
+        assert_eq!(Some(PathBuf::from("/home/dirk/.config")), get_base_dir(None, Some("/home/dirk".to_string()), ".config"));
+
+        assert_eq!(None, get_base_dir(None, None, ".config"));
+    }
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::Local;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::get_server_file_key;
+use crate::dirs::get_state_dir;
+
+const HISTORY_DIR: &str = "history";
+const HISTORY_FILE_PREFIX: &str = "builds";
+
+/// Jobs built with jencli, used for `list --sort recent-use`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct JobUsage {
+    pub job_name: String,
+    pub builds: u32,
+    /// Unix timestamp, seconds
+    pub last_build: i64,
+}
+
+/// History is kept per server, job names of different servers never mix.
+pub fn record_job_usage(jenkins_url: &str, job_name: &str) -> anyhow::Result<()> {
+    let mut usage = load_job_usage(jenkins_url)?;
+
+    let now = Local::now().timestamp();
+
+    match usage.iter_mut().find(|u| u.job_name == job_name) {
+        Some(job_usage) => {
+            job_usage.builds += 1;
+            job_usage.last_build = now;
+        }
+        None => usage.push(JobUsage { job_name: job_name.to_string(), builds: 1, last_build: now })
+    }
+
+    save_job_usage(jenkins_url, &usage)
+}
+
+pub fn load_job_usage(jenkins_url: &str) -> anyhow::Result<Vec<JobUsage>> {
+    let file_path = get_history_file_path(jenkins_url);
+
+    if file_path.exists() {
+        let json = fs::read_to_string(file_path)?;
+
+        let usage = serde_json::from_str(&json)?;
+        debug!("job usage loaded: {:?}", usage);
+
+        Ok(usage)
+
+    } else {
+        Ok(vec![])
+    }
+}
+
+fn save_job_usage(jenkins_url: &str, usage: &[JobUsage]) -> anyhow::Result<()> {
+    debug!("save job usage: {:?}", usage);
+    let history_path = get_state_dir().join(HISTORY_DIR);
+
+    if !history_path.exists() {
+        fs::create_dir_all(&history_path)?;
+    }
+
+    let json = serde_json::to_string(&usage)?;
+
+    fs::write(get_history_file_path(jenkins_url), json)?;
+
+    Ok(())
+}
+
+/// `builds-jenkins.company.com.json`
+fn get_history_file_path(jenkins_url: &str) -> PathBuf {
+    get_state_dir().join(HISTORY_DIR)
+        .join(format!("{HISTORY_FILE_PREFIX}-{}.json", get_server_file_key(jenkins_url)))
+}
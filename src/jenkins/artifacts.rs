@@ -0,0 +1,216 @@
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, Context};
+use log::info;
+use reqwest::blocking::{Client, Response};
+use serde::Deserialize;
+
+use crate::jenkins::{format_size, get_build_url};
+
+const ARCHIVE_FILE_NAME: &str = "archive.zip";
+
+const DOWNLOAD_BUFFER_SIZE: usize = 64 * 1024;
+
+/// File is downloaded as `<name>.part` and renamed once transfer is complete.
+const PARTIAL_FILE_EXTENSION: &str = "part";
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsBuildArtifacts {
+    pub number: u32,
+    pub artifacts: Vec<JenkinsArtifact>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsArtifact {
+    pub file_name: String,
+    pub relative_path: String,
+}
+
+pub fn get_build_artifacts(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                           job_name: &str, build: &str) -> anyhow::Result<JenkinsBuildArtifacts> {
+    info!("get artifacts of build '{build}' for job '{job_name}'..");
+
+    let url = format!("{}/api/json?tree=number,artifacts[fileName,relativePath]",
+                      get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get build '{build}' for job '{job_name}'"))?;
+
+    let artifacts = resp.json::<JenkinsBuildArtifacts>()?;
+
+    info!("artifacts: {:?}", artifacts);
+
+    Ok(artifacts)
+}
+
+/// Download single artifact into `dest` directory, relative path is kept.
+#[allow(clippy::too_many_arguments)]
+pub fn download_artifact(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                         job_name: &str, build: u32, artifact: &JenkinsArtifact,
+                         dest: &Path) -> anyhow::Result<PathBuf> {
+    let relative_path = artifact.relative_path.split('/')
+        .map(|part| urlencoding::encode(part).to_string())
+        .collect::<Vec<String>>()
+        .join("/");
+
+    let url = format!("{}/artifact/{relative_path}",
+                      get_build_url(jenkins_url, job_name, &build.to_string()));
+
+    let file_path = get_artifact_file_path(dest, &artifact.relative_path)?;
+
+    download(client, &url, username, token, &file_path)?;
+
+    Ok(file_path)
+}
+
+/// Download all build artifacts as single zip-archive into `dest` directory.
+pub fn download_artifacts_archive(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                                  job_name: &str, build: u32, dest: &Path) -> anyhow::Result<PathBuf> {
+    let url = format!("{}/artifact/*zip*/{ARCHIVE_FILE_NAME}",
+                      get_build_url(jenkins_url, job_name, &build.to_string()));
+
+    let file_path = dest.join(ARCHIVE_FILE_NAME);
+
+    download(client, &url, username, token, &file_path)?;
+
+    Ok(file_path)
+}
+
+/// Relative path comes from server, so `..`, absolute paths and other non-normal components are rejected.
+fn get_artifact_file_path(dest: &Path, relative_path: &str) -> anyhow::Result<PathBuf> {
+    let path = Path::new(relative_path);
+
+    if relative_path.is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(anyhow!("unsafe artifact path '{relative_path}'"))
+    }
+
+    Ok(dest.join(path))
+}
+
+fn download(client: &Client, url: &str, username: &str, token: &str,
+            file_path: &Path) -> anyhow::Result<()> {
+    info!("download '{url}' to '{}'..", file_path.display());
+
+    let resp = client.get(url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to download '{url}'"))?;
+
+    if let Some(parent) = file_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut partial_file_name = file_path.file_name().unwrap_or_default().to_os_string();
+    partial_file_name.push(format!(".{PARTIAL_FILE_EXTENSION}"));
+
+    let partial_file_path = file_path.with_file_name(partial_file_name);
+
+    let mut file = File::create(&partial_file_path)
+        .context(format!("unable to create file '{}'", partial_file_path.display()))?;
+
+    if let Err(e) = write_with_progress(resp, &mut file, &file_path.display().to_string()) {
+        let _ = fs::remove_file(&partial_file_path);
+        return Err(e.context(format!("unable to download '{url}'")))
+    }
+
+    fs::rename(&partial_file_path, file_path)
+        .context(format!("unable to move downloaded file to '{}'", file_path.display()))?;
+
+    info!("file '{}' has been downloaded", file_path.display());
+
+    Ok(())
+}
+
+/// Stream response body into file, progress is printed to stderr.
+fn write_with_progress(mut resp: Response, file: &mut File, label: &str) -> anyhow::Result<()> {
+    let total = resp.content_length();
+
+    let mut buffer = vec![0; DOWNLOAD_BUFFER_SIZE];
+    let mut downloaded: u64 = 0;
+
+    loop {
+        let read = resp.read(&mut buffer)?;
+
+        if read == 0 {
+            break
+        }
+
+        file.write_all(&buffer[..read])?;
+        downloaded += read as u64;
+
+        match total {
+            Some(total) if total > 0 =>
+                eprint!("\r{label}: {} / {} ({}%)", format_size(downloaded),
+                        format_size(total), downloaded * 100 / total),
+            _ => eprint!("\r{label}: {}", format_size(downloaded))
+        }
+    }
+
+    eprintln!();
+
+    Ok(())
+}
+
+/// Simple glob matching for artifact paths: `*` - any characters, `?` - single character.
+pub fn matches_glob(pattern: &str, value: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    let value = value.chars().collect::<Vec<char>>();
+
+    let (mut p, mut v) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, v));
+            p += 1;
+
+        } else if let Some((star_p, star_v)) = star {
+            p = star_p + 1;
+            v = star_v + 1;
+            star = Some((star_p, star_v + 1));
+
+        } else {
+            return false
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::jenkins::artifacts::{get_artifact_file_path, matches_glob};
+
+    #[test]
+    fn glob_should_match() {
+        assert!(matches_glob("*.msi", "installer/app-1.2.msi"));
+        assert!(matches_glob("installer/app-?.?.msi", "installer/app-1.2.msi"));
+        assert!(matches_glob("*", "app.zip"));
+        assert!(!matches_glob("*.msi", "installer/app-1.2.exe"));
+        assert!(!matches_glob("app", "app.zip"));
+    }
+
+    #[test]
+    fn unsafe_artifact_paths_should_be_rejected() {
+        let dest = Path::new("dist");
+
+        assert_eq!(Path::new("dist/installer/app.msi"), get_artifact_file_path(dest, "installer/app.msi").unwrap());
+
+        assert!(get_artifact_file_path(dest, "../app.msi").is_err());
+        assert!(get_artifact_file_path(dest, "installer/../../app.msi").is_err());
+        assert!(get_artifact_file_path(dest, "/etc/passwd").is_err());
+        assert!(get_artifact_file_path(dest, "./app.msi").is_err());
+        assert!(get_artifact_file_path(dest, "").is_err());
+    }
+}
@@ -1,106 +1,264 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
-use anyhow::anyhow;
-use log::info;
-use reqwest::blocking::Client;
+use anyhow::{anyhow, Context};
+use log::{info, warn};
+use reqwest::blocking::{Client, Response};
+use reqwest::header::LOCATION;
 use reqwest::StatusCode;
-use serde::{Deserialize, Deserializer, Serialize};
+use serde::{Deserialize, Deserializer};
 use urlencoding::encode;
 
-use crate::jenkins::auth::{get_crumb_issuer, JenkinsCrumbIssuer};
+use crate::jenkins::auth::get_crumb_issuer;
+use crate::jenkins::duplicates::{cancel_duplicate_builds, DuplicateBuildPolicy, DuplicateBuilds, find_duplicate_builds};
+use crate::jenkins::{get_build_url, get_job_url};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildTrigger {
+    /// Queue item url, see `wait_for_build_start`
+    Queued(String),
+    /// Job has queued or running builds with identical parameters
+    Skipped(DuplicateBuilds),
+}
+
+/// Build is skipped if job has duplicate builds and policy is `Skip` or `Confirm`,
+/// the latter should be resolved by caller with `confirm_duplicate_builds` before.
+///
+/// `delay` is jenkins quiet period, build stays in queue until it's over.
+///
+/// With `trigger_token` ("Trigger builds remotely" in job config) build is triggered without user credentials,
+/// they are used only to read the last build and check duplicate builds if allowed.
+#[allow(clippy::too_many_arguments)]
+pub fn build_job(client: &Client, jenkins_url: &str, username: &str, token: &str, job_name: &str,
+                 duplicates: DuplicateBuildPolicy, delay: Option<Duration>,
+                 trigger_token: Option<&str>) -> anyhow::Result<BuildTrigger> {
+    build_job_with_params(client, jenkins_url, username, token, job_name, &HashMap::new(),
+                          duplicates, delay, trigger_token)
+}
+
+/// Parameters of the latest build are used, `params` override them.
+#[allow(clippy::too_many_arguments)]
+pub fn build_job_with_params(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                             job_name: &str, params: &HashMap<String, String>,
+                             duplicates: DuplicateBuildPolicy, delay: Option<Duration>,
+                             trigger_token: Option<&str>) -> anyhow::Result<BuildTrigger> {
+    if let Some(trigger_token) = trigger_token {
+        return build_job_with_trigger_token(client, jenkins_url, username, token, job_name, params,
+                                            duplicates, delay, trigger_token)
+    }
 
-pub fn build_job(client: &Client, jenkins_url: &str, username: &str, token: &str, job_name: &str) -> anyhow::Result<()> {
     info!("attempt to build job '{job_name}' at '{jenkins_url}'..");
 
     info!("getting latest build information..");
 
-    let build_info = get_job_build_info(&client, &jenkins_url,
-                                        &username, &token, &job_name)?;
+    let build_info = get_job_build_info(client, jenkins_url,
+                                        username, token, job_name)?;
+
+    let crumb_issuer = get_crumb_issuer(client, jenkins_url,
+                                        username, token)?;
+
+    let build_params = get_build_params(&build_info, params);
+
+    if let Some(duplicate_builds) = check_duplicate_builds(client, jenkins_url, username, token, job_name,
+                                                           &build_params, duplicates)? {
+        return Ok(BuildTrigger::Skipped(duplicate_builds))
+    }
+
+    let mut url_params = get_url_params(&build_params,
+                                        &[(crumb_issuer.crumb_request_field, crumb_issuer.crumb)]);
 
-    let crumb_issuer = get_crumb_issuer(&client, &jenkins_url,
-                                        &username, &token)?;
+    if let Some(delay) = delay {
+        url_params.push_str(&format!("&delay={}sec", delay.as_secs()));
+    }
 
-    let url_params = get_url_params(&build_info, &crumb_issuer)?;
-    let url_params = encode(&url_params);
-    let url_params = format!("{url_params}");
+    let endpoint = if build_params.is_empty() { "build" } else { "buildWithParameters" };
 
-    let url = format!("https://jenkins.sk.ru/job/{job_name}/buildWithParameters?{}", url_params);
+    let url = format!("{}/{endpoint}?{}", get_job_url(jenkins_url, job_name), url_params);
 
     info!("url '{url}'");
 
-    let resp = client.post(url).basic_auth(&username, Some(token)).send()?;
+    let resp = client.post(url).basic_auth(username, Some(token)).send()?;
+
+    get_queue_item_url(resp, job_name).map(BuildTrigger::Queued)
+}
+
+/// Builds are skipped or cancelled by `duplicates` policy, returns skipped duplicate builds if any.
+#[allow(clippy::too_many_arguments)]
+fn check_duplicate_builds(client: &Client, jenkins_url: &str, username: &str, token: &str, job_name: &str,
+                          build_params: &[(String, String)],
+                          duplicates: DuplicateBuildPolicy) -> anyhow::Result<Option<DuplicateBuilds>> {
+    if duplicates == DuplicateBuildPolicy::Allow {
+        return Ok(None)
+    }
+
+    let duplicate_builds = find_duplicate_builds(client, jenkins_url, username, token,
+                                                 job_name, build_params)?;
+
+    if duplicate_builds.is_empty() {
+        return Ok(None)
+    }
+
+    warn!("job '{job_name}' has {duplicate_builds} with identical parameters");
+
+    match duplicates {
+        DuplicateBuildPolicy::Skip | DuplicateBuildPolicy::Confirm => {
+            info!("build for job '{job_name}' skipped, job isn't idle");
+            Ok(Some(duplicate_builds))
+        }
+        DuplicateBuildPolicy::CancelRunning => {
+            let crumb_issuer = get_crumb_issuer(client, jenkins_url, username, token)?;
+            cancel_duplicate_builds(client, jenkins_url, username, token, &crumb_issuer, &duplicate_builds)?;
+            Ok(None)
+        }
+        DuplicateBuildPolicy::Allow => Ok(None)
+    }
+}
 
+/// Parameters of the latest build are used only if user credentials are given (not empty) and allow to read it.
+/// Trigger token doesn't give access to job state, so duplicate builds are checked with user credentials only,
+/// without them `Skip` and `CancelRunning` policies are rejected.
+#[allow(clippy::too_many_arguments)]
+fn build_job_with_trigger_token(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                                job_name: &str, params: &HashMap<String, String>,
+                                duplicates: DuplicateBuildPolicy, delay: Option<Duration>,
+                                trigger_token: &str) -> anyhow::Result<BuildTrigger> {
+    info!("attempt to build job '{job_name}' at '{jenkins_url}' with trigger token..");
+
+    let has_credentials = !username.is_empty() && !token.is_empty();
+
+    if !has_credentials && matches!(duplicates, DuplicateBuildPolicy::Skip | DuplicateBuildPolicy::CancelRunning) {
+        return Err(anyhow!("duplicate builds of job '{job_name}' can't be checked without user credentials"))
+    }
+
+    let build_info = match has_credentials {
+        true => get_job_build_info(client, jenkins_url, username, token, job_name),
+        false => Err(anyhow!("user credentials aren't set"))
+    };
+
+    let build_params = match build_info {
+        Ok(build_info) => get_build_params(&build_info, params),
+        Err(e) => {
+            warn!("unable to get latest build of job '{job_name}', only given parameters are used: {e}");
+
+            let mut build_params = params.iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<Vec<(String, String)>>();
+
+            build_params.sort();
+
+            build_params
+        }
+    };
+
+    if has_credentials {
+        if let Some(duplicate_builds) = check_duplicate_builds(client, jenkins_url, username, token, job_name,
+                                                               &build_params, duplicates)? {
+            return Ok(BuildTrigger::Skipped(duplicate_builds))
+        }
+    }
+
+    let mut url_params = get_url_params(&build_params, &[("token".to_string(), trigger_token.to_string())]);
+
+    if let Some(delay) = delay {
+        url_params.push_str(&format!("&delay={}sec", delay.as_secs()));
+    }
+
+    let endpoint = if build_params.is_empty() { "build" } else { "buildWithParameters" };
+
+    let url = format!("{}/{endpoint}?{}", get_job_url(jenkins_url, job_name), url_params);
+
+    let resp = client.post(url).send()?;
+
+    get_queue_item_url(resp, job_name).map(BuildTrigger::Queued)
+}
+
+fn get_queue_item_url(resp: Response, job_name: &str) -> anyhow::Result<String> {
     let status = resp.status();
 
     info!("server response: {}", status);
 
     if status == StatusCode::CREATED {
         info!("build for job '{job_name}' successfully executed");
-        Ok(())
+
+        let queue_item_url = resp.headers().get(LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or(anyhow!("queue item location is missing in server response"))?;
+
+        Ok(queue_item_url.to_string())
 
     } else {
         Err(anyhow!("unexpected server response"))
     }
 }
 
-fn get_job_build_info(client: &Client, jenkins_url: &str, username: &str,
-                      token: &str, job_name: &str) -> anyhow::Result<JenkinsBuildInfo> {
-    let url = format!("{jenkins_url}/job/{job_name}/lastBuild/api/json");
-
-    let resp = client.get(&url).basic_auth(&username, Some(token)).send()?;
-
-    let build_info = resp.json::<JenkinsBuildInfo>()?;
-
-    info!("build info: {:?}", build_info);
+/// Parameters of build, used to re-trigger build with the same parameters.
+pub fn get_build_parameters(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                            job_name: &str, number: u32) -> anyhow::Result<HashMap<String, String>> {
+    let build_info = get_build_info(client, jenkins_url, username, token, job_name, &number.to_string())?;
 
-    Ok(build_info)
+    Ok(get_build_params(&build_info, &HashMap::new()).into_iter().collect())
 }
 
-/// name=SCM_BRANCH&value=dev&name=CLEAR_DOCKER_CACHE&name=UPDATE_DB_RIGHTS&Jenkins-Crumb=0e1dcbfe09b0ceb2c47b95c0df172b7a0d976b49782a75adbed124ef526df6a1&json=%7B%22parameter%22%3A+%5B%7B%22name%22%3A+%22SCM_BRANCH%22%2C+%22va    lue%22%3A+%22dev%22%7D%2C+%7B%22name%22%3A+%22CLEAR_DOCKER_CACHE%22%2C+%22value%22%3A+false%7D%2C+%7B%22name%22%3A+%22UPDATE_DB_RIGHTS%22%2C+%22value%22%3A+false%7D%5D%2C+%22Jenkins-Crumb%22%3A+%220e1dcbfe09b0ceb2c47b95c0df1    72b7a0d976b49782a75adbed124ef526df6a1%22%7D&Submit=Rebuild
-fn get_url_params(build_info: &JenkinsBuildInfo,
-                  crumb_issuer: &JenkinsCrumbIssuer) -> anyhow::Result<String> {
-
-    let mut result: Vec<String> = vec![];
+/// Parameters build would be triggered with: parameters of the latest build with `params` overrides.
+pub fn get_next_build_params(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                             job_name: &str, params: &HashMap<String, String>) -> anyhow::Result<Vec<(String, String)>> {
+    let build_info = get_job_build_info(client, jenkins_url, username, token, job_name)?;
 
-    if let Some(action) = build_info.actions.iter().find(|a| a.parameters.is_some()) {
-        let params = action.parameters.clone().unwrap();
+    Ok(get_build_params(&build_info, params))
+}
 
-        let mut json_props: Vec<HashMap<String, String>> = vec![];
+fn get_job_build_info(client: &Client, jenkins_url: &str, username: &str,
+                      token: &str, job_name: &str) -> anyhow::Result<JenkinsBuildInfo> {
+    get_build_info(client, jenkins_url, username, token, job_name, "lastBuild")
+}
 
-        params.iter().for_each(|p| {
-            result.push(format!("name={}", p.name));
-            result.push(format!("value={}", p.value));
+fn get_build_info(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                  job_name: &str, build: &str) -> anyhow::Result<JenkinsBuildInfo> {
+    let url = format!("{}/api/json", get_build_url(jenkins_url, job_name, build));
 
-            json_props.push(HashMap::from([(p.name.to_string(), p.value.to_string())]))
-        });
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get build '{build}' of job '{job_name}'"))?;
 
-        result.push(format!("{}={}", crumb_issuer.crumb_request_field, crumb_issuer.crumb));
+    let build_info = resp.json::<JenkinsBuildInfo>()?;
 
-        let rebuild_url_params = JenkinsRebuildUrlParams {
-            parameter: json_props.clone()
-        };
+    info!("build #{} '{}': {:?}", build_info.number, build_info.url, build_info);
 
-        let mut json_param = serde_json::to_string(&rebuild_url_params)?;
+    Ok(build_info)
+}
 
-        json_param = json_param.replace("\"true\"", "true")
-                               .replace("\"false\"", "false");
+/// Parameters of the latest build with overrides, order of the latest build is kept.
+fn get_build_params(build_info: &JenkinsBuildInfo, overrides: &HashMap<String, String>) -> Vec<(String, String)> {
+    let mut result: Vec<(String, String)> = build_info.actions.iter()
+        .find_map(|a| a.parameters.clone())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| {
+            let value = overrides.get(&p.name).cloned().unwrap_or(p.value);
+            (p.name, value)
+        })
+        .collect();
 
-        result.push(format!("json={json_param}"));
-    }
+    let mut new_params = overrides.iter()
+        .filter(|(name, _)| !result.iter().any(|(n, _)| n == *name))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect::<Vec<(String, String)>>();
 
-    let mut result = result.join("&").to_string();
+    new_params.sort();
 
-    if result.ends_with("&") {
-        result.pop();
-    }
+    result.extend(new_params);
 
-    Ok(result.to_string())
+    result
 }
 
-#[derive(Serialize, Debug, Clone)]
-#[serde(rename_all = "camelCase")]
-struct JenkinsRebuildUrlParams {
-    pub parameter: Vec<HashMap<String, String>>
+/// SCM_BRANCH=dev&CLEAR_DOCKER_CACHE=false&Jenkins-Crumb=0e1dcbfe09b0ceb2c47b95c0df172b7a0d976b49782a75adbed124ef526df6a1
+///
+/// `auth` is crumb or trigger token.
+fn get_url_params(params: &[(String, String)], auth: &[(String, String)]) -> String {
+    params.iter().chain(auth)
+        .map(|(name, value)| format!("{}={}", encode(name), encode(value)))
+        .collect::<Vec<String>>()
+        .join("&")
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -119,15 +277,12 @@ struct JenkinsBuildAction {
 
 #[derive(Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
-struct JenkinsBuildParam {
+pub struct JenkinsBuildParam {
     pub name: String,
     #[serde(deserialize_with = "str_or_bool")]
     pub value: String,
 }
 
-#[derive(Deserialize, Debug, Clone)]
-struct JenkinsBuildParamValue;
-
 fn str_or_bool<'de, D>(deserializer: D) -> Result<String, D::Error>
     where
         D: Deserializer<'de>,
@@ -145,6 +300,65 @@ fn str_or_bool<'de, D>(deserializer: D) -> Result<String, D::Error>
     })
 }
 
+#[cfg(test)]
+mod url_params_tests {
+    use std::collections::HashMap;
+
+    use crate::jenkins::build::{get_build_params, get_url_params, JenkinsBuildAction, JenkinsBuildInfo, JenkinsBuildParam};
+
+    #[test]
+    fn overrides_should_be_applied() {
+        let build_info = JenkinsBuildInfo {
+            number: 42,
+            url: "https://jenkins.company.com/job/api/42/".to_string(),
+            actions: vec![
+                JenkinsBuildAction { parameters: None },
+                JenkinsBuildAction {
+                    parameters: Some(vec![
+                        JenkinsBuildParam { name: "SCM_BRANCH".to_string(), value: "dev".to_string() },
+                        JenkinsBuildParam { name: "CLEAR_DOCKER_CACHE".to_string(), value: "false".to_string() },
+                    ])
+                },
+            ],
+        };
+
+        let overrides = HashMap::from([
+            ("SCM_BRANCH".to_string(), "feature/login".to_string()),
+            ("NEW_PARAM".to_string(), "1".to_string()),
+        ]);
+
+        let params = get_build_params(&build_info, &overrides);
+
+        assert_eq!("SCM_BRANCH=feature%2Flogin&CLEAR_DOCKER_CACHE=false&NEW_PARAM=1&Jenkins-Crumb=abc",
+                   get_url_params(&params, &[("Jenkins-Crumb".to_string(), "abc".to_string())]));
+
+        assert_eq!("token=a%26b", get_url_params(&[], &[("token".to_string(), "a&b".to_string())]));
+    }
+}
+
+#[cfg(test)]
+mod trigger_token_tests {
+    use std::collections::HashMap;
+
+    use reqwest::blocking::Client;
+
+    use crate::jenkins::build::build_job_with_params;
+    use crate::jenkins::duplicates::DuplicateBuildPolicy;
+
+    #[test]
+    fn duplicate_check_without_credentials_should_be_rejected() {
+        let client = Client::new();
+
+        for duplicates in [DuplicateBuildPolicy::Skip, DuplicateBuildPolicy::CancelRunning] {
+            let result = build_job_with_params(&client, "http://localhost:1", "", "", "api", &HashMap::new(),
+                                               duplicates, None, Some("secret"));
+
+            assert_eq!("duplicate builds of job 'api' can't be checked without user credentials",
+                       result.unwrap_err().to_string());
+        }
+    }
+}
+
 #[cfg(test)]
 mod params_deserialization_tests {
     use crate::jenkins::build::JenkinsBuildParam;
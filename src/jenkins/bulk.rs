@@ -0,0 +1,444 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use log::{error, info, warn};
+use reqwest::blocking::Client;
+
+use crate::config::{find_trigger_token, TriggerToken};
+use crate::jenkins::build::{build_job_with_params, BuildTrigger, get_build_parameters};
+use crate::jenkins::describe::set_build_description;
+use crate::jenkins::duplicates::{confirm_duplicate_builds, DuplicateBuildPolicy};
+use crate::jenkins::format_duration;
+use crate::jenkins::wait::{wait_for_build_result, wait_for_build_start};
+
+const QUEUED_RESULT: &str = "QUEUED";
+const SKIPPED_RESULT: &str = "SKIPPED";
+const ERROR_RESULT: &str = "ERROR";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildOutcome {
+    pub job_name: String,
+    pub number: Option<u32>,
+    /// Jenkins build result, `QUEUED` (without waiting for result), `SKIPPED` or `ERROR` (jencli failed to trigger or wait)
+    pub result: String,
+    pub duration: Option<u64>,
+    pub error: Option<String>,
+    /// Build numbers of previous attempts, see `RetryPolicy`
+    pub retried: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RetryBackoff {
+    Fixed,
+    /// Delay is doubled after each attempt
+    Exponential,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    /// Build results to retry on: `FAILURE`, `ABORTED`, etc.
+    pub on: Vec<String>,
+    pub delay: Duration,
+    pub backoff: RetryBackoff,
+}
+
+impl RetryPolicy {
+    pub fn none() -> RetryPolicy {
+        RetryPolicy {
+            retries: 0,
+            on: vec![],
+            delay: Duration::ZERO,
+            backoff: RetryBackoff::Fixed,
+        }
+    }
+
+    fn should_retry(&self, outcome: &BuildOutcome, attempt: u32) -> bool {
+        attempt <= self.retries && outcome.number.is_some() && self.on.contains(&outcome.result)
+    }
+
+    /// Delay before attempt (starts from 1)
+    fn get_delay(&self, attempt: u32) -> Duration {
+        match self.backoff {
+            RetryBackoff::Fixed => self.delay,
+            RetryBackoff::Exponential => self.delay * 2_u32.saturating_pow(attempt - 1),
+        }
+    }
+}
+
+impl BuildOutcome {
+    pub fn is_failed(&self) -> bool {
+        ![QUEUED_RESULT, SKIPPED_RESULT, "SUCCESS"].contains(&self.result.as_str())
+    }
+
+    /// All attempts: `#41, #42`
+    pub fn get_build_numbers(&self) -> String {
+        let numbers = self.retried.iter().chain(self.number.iter())
+            .map(|n| format!("#{n}"))
+            .collect::<Vec<String>>();
+
+        if numbers.is_empty() {
+            "-".to_string()
+
+        } else {
+            numbers.join(", ")
+        }
+    }
+
+    /// Build was queued or started, even if waiting for it has failed.
+    pub fn is_triggered(&self) -> bool {
+        self.number.is_some() || self.result == QUEUED_RESULT
+    }
+
+    pub fn skipped(job_name: &str) -> BuildOutcome {
+        BuildOutcome {
+            job_name: job_name.to_string(),
+            number: None,
+            result: SKIPPED_RESULT.to_string(),
+            duration: None,
+            error: None,
+            retried: vec![],
+        }
+    }
+
+    pub fn from_error(job_name: &str, number: Option<u32>, e: anyhow::Error) -> BuildOutcome {
+        error!("job '{job_name}' error: {e}");
+
+        BuildOutcome {
+            job_name: job_name.to_string(),
+            number,
+            result: ERROR_RESULT.to_string(),
+            duration: None,
+            error: Some(format!("{e}: {}", e.root_cause())),
+            retried: vec![],
+        }
+    }
+}
+
+/// Duplicate builds are confirmed before triggering on calling thread (main thread), so prompts don't interleave.
+/// Returns `None` if build should be triggered, otherwise skipped or error outcome.
+pub fn confirm_build(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                     job_name: &str, params: &HashMap<String, String>,
+                     trigger_tokens: &[TriggerToken]) -> Option<BuildOutcome> {
+    let with_trigger_token = find_trigger_token(trigger_tokens, job_name).is_some();
+
+    // Duplicates of jobs triggered with token are checked only with user credentials
+    if with_trigger_token && (username.is_empty() || token.is_empty()) {
+        return None
+    }
+
+    match confirm_duplicate_builds(client, jenkins_url, username, token, job_name, params) {
+        Ok(true) => None,
+        Ok(false) => {
+            println!("[{job_name}] build skipped");
+            Some(BuildOutcome::skipped(job_name))
+        }
+        // Credentials might not allow to read the job, token is enough to trigger it
+        Err(e) if with_trigger_token => {
+            warn!("unable to check duplicate builds of job '{job_name}': {e}");
+            None
+        }
+        Err(e) => Some(BuildOutcome::from_error(job_name, None, e))
+    }
+}
+
+/// Trigger build with parameters from the last build and `params` overrides, optionally wait for result.
+/// `DuplicateBuildPolicy::Confirm` should be resolved with `confirm_build` before, otherwise build is skipped.
+/// Description is set once build is started, so start is awaited for it even without `wait`. `delay` is jenkins quiet period.
+#[allow(clippy::too_many_arguments)]
+pub fn trigger_build(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                     job_name: &str, params: &HashMap<String, String>, wait: bool,
+                     duplicates: DuplicateBuildPolicy, delay: Option<Duration>, description: Option<&str>,
+                     trigger_tokens: &[TriggerToken]) -> BuildOutcome {
+    let queue_item_url = match build_job_with_params(client, jenkins_url, username, token,
+                                                     job_name, params, duplicates, delay,
+                                                     find_trigger_token(trigger_tokens, job_name)) {
+        Ok(BuildTrigger::Queued(queue_item_url)) => queue_item_url,
+        Ok(BuildTrigger::Skipped(duplicate_builds)) => {
+            println!("[{job_name}] build skipped, job has {duplicate_builds} with identical parameters");
+            return BuildOutcome::skipped(job_name)
+        }
+        Err(e) => return BuildOutcome::from_error(job_name, None, e)
+    };
+
+    println!("[{job_name}] build queued");
+
+    if !wait && description.is_none() {
+        return BuildOutcome {
+            job_name: job_name.to_string(),
+            number: None,
+            result: QUEUED_RESULT.to_string(),
+            duration: None,
+            error: None,
+            retried: vec![],
+        }
+    }
+
+    let build = match wait_for_build_start(client, username, token, &queue_item_url) {
+        Ok(build) => build,
+        Err(e) => return BuildOutcome::from_error(job_name, None, e)
+    };
+
+    println!("[{job_name}] build #{} started: {}", build.number, build.url);
+
+    if let Some(description) = description {
+        // Build is already triggered, so failure isn't fatal
+        match set_build_description(client, jenkins_url, username, token, job_name,
+                                    &build.number.to_string(), description) {
+            Ok(_) => println!("[{job_name}] description of build #{} updated", build.number),
+            Err(e) => eprintln!("[{job_name}] unable to set description of build #{}: {e}", build.number)
+        }
+    }
+
+    if !wait {
+        return BuildOutcome {
+            job_name: job_name.to_string(),
+            number: Some(build.number),
+            result: QUEUED_RESULT.to_string(),
+            duration: None,
+            error: None,
+            retried: vec![],
+        }
+    }
+
+    match wait_for_build_result(client, username, token, &build.url) {
+        Ok(result) => {
+            let outcome = BuildOutcome {
+                job_name: job_name.to_string(),
+                number: Some(result.number),
+                result: result.result.unwrap_or(ERROR_RESULT.to_string()),
+                duration: Some(result.duration),
+                error: None,
+                retried: vec![],
+            };
+
+            println!("[{job_name}] build #{} finished: {}", result.number, outcome.result);
+
+            outcome
+        }
+        Err(e) => BuildOutcome::from_error(job_name, Some(build.number), e)
+    }
+}
+
+/// Trigger build and wait for result, failed build is re-triggered with the same parameters according to policy.
+/// Description is set for every attempt, `delay` is applied to the first attempt only, see `RetryPolicy`.
+#[allow(clippy::too_many_arguments)]
+pub fn trigger_build_with_retry(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                                job_name: &str, params: &HashMap<String, String>,
+                                retry: &RetryPolicy, duplicates: DuplicateBuildPolicy, delay: Option<Duration>,
+                                description: Option<&str>, trigger_tokens: &[TriggerToken]) -> BuildOutcome {
+    let mut outcome = trigger_build(client, jenkins_url, username, token, job_name, params, true,
+                                    duplicates, delay, description, trigger_tokens);
+    let mut retried: Vec<u32> = vec![];
+    let mut attempt = 1;
+
+    while retry.should_retry(&outcome, attempt) {
+        let number = outcome.number.unwrap();
+        let delay = retry.get_delay(attempt);
+
+        println!("[{job_name}] build #{number} {}, retry {attempt}/{} in {}", outcome.result, retry.retries,
+                 format_duration(delay.as_millis() as u64));
+
+        thread::sleep(delay);
+
+        let params = get_build_parameters(client, jenkins_url, username, token, job_name, number)
+            .unwrap_or_else(|e| {
+                error!("unable to get parameters of build #{number} for job '{job_name}': {e}");
+                params.clone()
+            });
+
+        retried.push(number);
+
+        outcome = trigger_build(client, jenkins_url, username, token, job_name, &params, true,
+                                DuplicateBuildPolicy::Allow, None, description, trigger_tokens);
+        attempt += 1;
+    }
+
+    outcome.retried = retried;
+
+    outcome
+}
+
+/// Trigger builds for jobs, not more than `parallel` at once. Outcomes are in the same order as jobs.
+/// With `DuplicateBuildPolicy::Confirm` duplicates of all jobs are confirmed before the first build is triggered.
+#[allow(clippy::too_many_arguments)]
+pub fn trigger_builds(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                      job_names: &[String], parallel: usize, wait: bool,
+                      retry: &RetryPolicy, duplicates: DuplicateBuildPolicy, delay: Option<Duration>,
+                      trigger_tokens: &[TriggerToken]) -> Vec<BuildOutcome> {
+    info!("trigger builds for {} jobs, parallel {parallel}, wait {wait}", job_names.len());
+
+    let mut queue: VecDeque<(usize, String)> = VecDeque::new();
+    let mut declined: Vec<(usize, BuildOutcome)> = vec![];
+
+    for (index, job_name) in job_names.iter().enumerate() {
+        let confirmed = match duplicates {
+            DuplicateBuildPolicy::Confirm => confirm_build(client, jenkins_url, username, token, job_name,
+                                                           &HashMap::new(), trigger_tokens),
+            _ => None
+        };
+
+        match confirmed {
+            Some(outcome) => declined.push((index, outcome)),
+            None => queue.push_back((index, job_name.to_string()))
+        }
+    }
+
+    let duplicates = match duplicates {
+        DuplicateBuildPolicy::Confirm => DuplicateBuildPolicy::Allow,
+        duplicates => duplicates
+    };
+
+    let workers_count = parallel.max(1).min(queue.len());
+
+    let queue = Arc::new(Mutex::new(queue));
+    let outcomes: Arc<Mutex<Vec<(usize, BuildOutcome)>>> = Arc::new(Mutex::new(declined));
+
+    let workers = (0..workers_count).map(|_| {
+        let queue = queue.clone();
+        let outcomes = outcomes.clone();
+
+        let client = client.clone();
+        let jenkins_url = jenkins_url.to_string();
+        let username = username.to_string();
+        let token = token.to_string();
+        let retry = retry.clone();
+        let trigger_tokens = trigger_tokens.to_vec();
+
+        thread::spawn(move || loop {
+            let next = queue.lock().unwrap().pop_front();
+
+            match next {
+                Some((index, job_name)) => {
+                    let outcome = if wait {
+                        trigger_build_with_retry(&client, &jenkins_url, &username, &token,
+                                                 &job_name, &HashMap::new(), &retry, duplicates, delay, None,
+                                                 &trigger_tokens)
+                    } else {
+                        trigger_build(&client, &jenkins_url, &username, &token,
+                                      &job_name, &HashMap::new(), false, duplicates, delay, None, &trigger_tokens)
+                    };
+                    outcomes.lock().unwrap().push((index, outcome));
+                }
+                None => break
+            }
+        })
+    }).collect::<Vec<_>>();
+
+    workers.into_iter().for_each(|w| w.join().expect("build worker has panicked"));
+
+    let mut outcomes = outcomes.lock().unwrap().drain(..).collect::<Vec<(usize, BuildOutcome)>>();
+    outcomes.sort_by_key(|(index, _)| *index);
+
+    outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+}
+
+/// Summary table for build outcomes.
+pub struct BuildSummary<'a>(pub &'a [BuildOutcome]);
+
+impl Display for BuildSummary<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut rows = vec![
+            ["JOB", "BUILD", "RESULT", "DURATION", ""].map(|h| h.to_string()).to_vec()
+        ];
+
+        self.0.iter().for_each(|o| rows.push(vec![
+            o.job_name.to_string(),
+            o.get_build_numbers(),
+            o.result.to_string(),
+            o.duration.map(format_duration).unwrap_or("-".to_string()),
+            o.error.clone().unwrap_or_default(),
+        ]));
+
+        write!(f, "{}", format_table(&rows))
+    }
+}
+
+/// Columns are aligned by the widest value, first row is header.
+pub fn format_table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+
+    let widths = (0..columns)
+        .map(|i| rows.iter().map(|r| r.get(i).map(|v| v.chars().count()).unwrap_or(0)).max().unwrap_or(0))
+        .collect::<Vec<usize>>();
+
+    rows.iter().map(|r| {
+        r.iter().enumerate()
+            .map(|(i, v)| format!("{v:width$}", width = widths[i]))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    }).collect::<Vec<String>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::jenkins::bulk::{BuildOutcome, BuildSummary, RetryBackoff, RetryPolicy};
+
+    #[test]
+    fn summary_table_should_be_aligned() {
+        let outcomes = vec![
+            get_outcome("api-deploy", Some(42), "SUCCESS", Some(65_000), None),
+            get_outcome("web", None, "ERROR", None, Some("unexpected server response")),
+        ];
+
+        assert_eq!("JOB         BUILD  RESULT   DURATION\n\
+                    api-deploy  #42    SUCCESS  1m 5s\n\
+                    web         -      ERROR    -         unexpected server response",
+                   BuildSummary(&outcomes).to_string());
+    }
+
+    #[test]
+    fn queued_and_successful_outcomes_should_not_be_failed() {
+        assert!(!get_outcome("a", None, "QUEUED", None, None).is_failed());
+        assert!(!get_outcome("a", Some(1), "SUCCESS", Some(1), None).is_failed());
+        assert!(get_outcome("a", Some(1), "UNSTABLE", Some(1), None).is_failed());
+        assert!(get_outcome("a", None, "ERROR", None, Some("e")).is_failed());
+        assert!(!BuildOutcome::skipped("a").is_failed());
+    }
+
+    #[test]
+    fn retry_policy_should_be_applied() {
+        let retry = RetryPolicy {
+            retries: 2,
+            on: vec!["FAILURE".to_string(), "ABORTED".to_string()],
+            delay: Duration::from_secs(10),
+            backoff: RetryBackoff::Exponential,
+        };
+
+        assert!(retry.should_retry(&get_outcome("a", Some(1), "FAILURE", Some(1), None), 1));
+        assert!(retry.should_retry(&get_outcome("a", Some(2), "ABORTED", Some(1), None), 2));
+        assert!(!retry.should_retry(&get_outcome("a", Some(3), "FAILURE", Some(1), None), 3));
+        assert!(!retry.should_retry(&get_outcome("a", Some(1), "UNSTABLE", Some(1), None), 1));
+        assert!(!retry.should_retry(&get_outcome("a", None, "ERROR", None, Some("e")), 1));
+
+        assert_eq!(Duration::from_secs(10), retry.get_delay(1));
+        assert_eq!(Duration::from_secs(40), retry.get_delay(3));
+    }
+
+    #[test]
+    fn all_attempts_should_be_shown() {
+        let mut outcome = get_outcome("a", Some(43), "SUCCESS", Some(1), None);
+        outcome.retried = vec![41, 42];
+
+        assert_eq!("#41, #42, #43", outcome.get_build_numbers());
+    }
+
+    fn get_outcome(job_name: &str, number: Option<u32>, result: &str, duration: Option<u64>,
+                   error: Option<&str>) -> BuildOutcome {
+        BuildOutcome {
+            job_name: job_name.to_string(),
+            number,
+            result: result.to_string(),
+            duration,
+            error: error.map(|e| e.to_string()),
+            retried: vec![],
+        }
+    }
+}
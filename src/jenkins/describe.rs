@@ -0,0 +1,104 @@
+use anyhow::Context;
+use log::info;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::jenkins::auth::get_crumb_issuer;
+use crate::jenkins::get_build_url;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsBuildDescription {
+    pub description: Option<String>,
+}
+
+pub fn set_build_description(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                             job_name: &str, build: &str, description: &str) -> anyhow::Result<()> {
+    info!("set description of build '{build}' for job '{job_name}'..");
+
+    let crumb_issuer = get_crumb_issuer(client, jenkins_url, username, token)?;
+
+    let form = vec![
+        ("description".to_string(), description.to_string()),
+        (crumb_issuer.crumb_request_field, crumb_issuer.crumb),
+    ];
+
+    let url = format!("{}/submitDescription", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.post(&url).basic_auth(username, Some(token)).form(&form).send()?;
+
+    info!("server response: {}", resp.status());
+
+    resp.error_for_status()
+        .context(format!("unable to set description of build '{build}' for job '{job_name}'"))?;
+
+    Ok(())
+}
+
+/// Build config form contains both fields, current description is kept if `description` isn't set.
+#[allow(clippy::too_many_arguments)]
+pub fn set_build_display_name(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                              job_name: &str, build: &str, display_name: &str,
+                              description: Option<&str>) -> anyhow::Result<()> {
+    info!("set display name of build '{build}' for job '{job_name}'..");
+
+    let description = match description {
+        Some(description) => description.to_string(),
+        None => get_build_description(client, jenkins_url, username, token, job_name, build)?
+    };
+
+    let crumb_issuer = get_crumb_issuer(client, jenkins_url, username, token)?;
+
+    let mut form = get_config_form(display_name, &description);
+    form.push((crumb_issuer.crumb_request_field, crumb_issuer.crumb));
+
+    let url = format!("{}/configSubmit", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.post(&url).basic_auth(username, Some(token)).form(&form).send()?;
+
+    info!("server response: {}", resp.status());
+
+    resp.error_for_status()
+        .context(format!("unable to set display name of build '{build}' for job '{job_name}'"))?;
+
+    Ok(())
+}
+
+fn get_build_description(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                         job_name: &str, build: &str) -> anyhow::Result<String> {
+    let url = format!("{}/api/json?tree=description", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get description of build '{build}' for job '{job_name}'"))?;
+
+    Ok(resp.json::<JenkinsBuildDescription>()?.description.unwrap_or_default())
+}
+
+fn get_config_form(display_name: &str, description: &str) -> Vec<(String, String)> {
+    vec![
+        ("displayName".to_string(), display_name.to_string()),
+        ("description".to_string(), description.to_string()),
+        ("json".to_string(), json!({"displayName": display_name, "description": description}).to_string()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use crate::jenkins::describe::get_config_form;
+
+    #[test]
+    fn config_form_should_contain_display_name_and_description() {
+        let form = get_config_form("1.4.0 \"release\"", "TICKET-123");
+
+        assert_eq!(("displayName".to_string(), "1.4.0 \"release\"".to_string()), form[0]);
+
+        let json = serde_json::from_str::<Value>(&form[2].1).unwrap();
+
+        assert_eq!("1.4.0 \"release\"", json["displayName"]);
+        assert_eq!("TICKET-123", json["description"]);
+    }
+}
@@ -0,0 +1,376 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::{anyhow, Context};
+use log::{info, warn};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+use crate::jenkins::build::JenkinsBuildParam;
+use crate::jenkins::{format_duration, get_build_url, get_job_url};
+
+const CHANGE_SET_TREE: &str = "items[commitId,msg,author[fullName]]";
+
+pub fn get_builds_diff(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                       job_name: &str, from: u32, to: u32) -> anyhow::Result<JenkinsBuildsDiff> {
+    info!("diff builds #{from} and #{to} for job '{job_name}'..");
+
+    if from >= to {
+        return Err(anyhow!("build #{from} should be older than build #{to}"))
+    }
+
+    let from_build = get_build_details(client, jenkins_url, username, token, job_name, from)?;
+    let to_build = get_build_details(client, jenkins_url, username, token, job_name, to)?;
+
+    let mut commits: Vec<JenkinsCommit> = vec![];
+
+    if to - from > 1 {
+        match get_intermediate_builds(client, jenkins_url, username, token, job_name, from, to) {
+            Ok(builds) => commits.extend(get_intermediate_commits(builds, from, to)),
+            Err(e) => warn!("unable to get builds between #{from} and #{to}, their changes are skipped: {e}")
+        }
+    }
+
+    commits.extend(to_build.get_commits());
+
+    Ok(JenkinsBuildsDiff {
+        from,
+        to,
+        parameters: get_parameter_changes(&from_build.get_parameters(), &to_build.get_parameters()),
+        commits,
+        result: (from_build.result.clone(), to_build.result.clone()),
+        duration: (from_build.duration, to_build.duration),
+        tests: (from_build.get_test_counts(), to_build.get_test_counts()),
+    })
+}
+
+fn get_build_details(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                     job_name: &str, number: u32) -> anyhow::Result<JenkinsBuildDetails> {
+    let url = format!("{}/api/json", get_build_url(jenkins_url, job_name, &number.to_string()));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get build #{number} for job '{job_name}'"))?;
+
+    let build = resp.json::<JenkinsBuildDetails>()?;
+
+    info!("build details: {:?}", build);
+
+    Ok(build)
+}
+
+/// Changes of all builds are fetched with single request. Builds are listed newest first and range is by index,
+/// index of build is at most `lastBuild - number` (builds might be deleted), so `{0,lastBuild - from}` covers them.
+fn get_intermediate_builds(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                           job_name: &str, from: u32, to: u32) -> anyhow::Result<Vec<JenkinsBuildChanges>> {
+    let job_url = get_job_url(jenkins_url, job_name);
+
+    let url = format!("{job_url}/api/json?tree=lastBuild[number]");
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get last build of job '{job_name}'"))?;
+
+    let last_build = resp.json::<JenkinsJobBuilds>()?.last_build
+        .map(|b| b.number)
+        .unwrap_or(to);
+
+    let url = format!("{job_url}/api/json?tree=builds[number,changeSets[{CHANGE_SET_TREE}],changeSet[{CHANGE_SET_TREE}]]{{0,{}}}",
+                      last_build.saturating_sub(from));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get builds between #{from} and #{to} for job '{job_name}'"))?;
+
+    let builds = resp.json::<JenkinsJobBuilds>()?.builds;
+
+    info!("builds with changes: {:?}", builds);
+
+    Ok(builds)
+}
+
+/// Commits of builds between `from` and `to` (both excluded), oldest build first.
+fn get_intermediate_commits(builds: Vec<JenkinsBuildChanges>, from: u32, to: u32) -> Vec<JenkinsCommit> {
+    let mut builds = builds.into_iter()
+        .filter(|b| b.number > from && b.number < to)
+        .collect::<Vec<JenkinsBuildChanges>>();
+
+    builds.sort_by_key(|b| b.number);
+
+    builds.iter()
+        .flat_map(|b| collect_commits(&b.change_sets, &b.change_set))
+        .collect()
+}
+
+fn collect_commits(change_sets: &Option<Vec<JenkinsChangeSet>>,
+                   change_set: &Option<JenkinsChangeSet>) -> Vec<JenkinsCommit> {
+    let mut commits: Vec<JenkinsCommit> = vec![];
+
+    if let Some(change_sets) = change_sets {
+        change_sets.iter().for_each(|cs| commits.extend(cs.items.clone()));
+    }
+
+    if let Some(change_set) = change_set {
+        commits.extend(change_set.items.clone());
+    }
+
+    commits
+}
+
+fn get_parameter_changes(from: &[JenkinsBuildParam], to: &[JenkinsBuildParam]) -> Vec<ParameterChange> {
+    let mut changes: Vec<ParameterChange> = vec![];
+
+    for param in to {
+        match from.iter().find(|p| p.name == param.name) {
+            Some(old) => if old.value != param.value {
+                changes.push(ParameterChange::Changed(param.name.to_string(),
+                                                      old.value.to_string(), param.value.to_string()))
+            },
+            None => changes.push(ParameterChange::Added(param.name.to_string(), param.value.to_string()))
+        }
+    }
+
+    from.iter()
+        .filter(|p| !to.iter().any(|n| n.name == p.name))
+        .for_each(|p| changes.push(ParameterChange::Removed(p.name.to_string(), p.value.to_string())));
+
+    changes
+}
+
+pub struct JenkinsBuildsDiff {
+    pub from: u32,
+    pub to: u32,
+    pub parameters: Vec<ParameterChange>,
+    pub commits: Vec<JenkinsCommit>,
+    pub result: (Option<String>, Option<String>),
+    pub duration: (u64, u64),
+    pub tests: (Option<JenkinsTestCounts>, Option<JenkinsTestCounts>),
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ParameterChange {
+    Added(String, String),
+    Removed(String, String),
+    Changed(String, String, String),
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsBuildDetails {
+    pub result: Option<String>,
+    pub duration: u64,
+    pub actions: Vec<JenkinsBuildDetailsAction>,
+    /// Pipeline jobs
+    pub change_sets: Option<Vec<JenkinsChangeSet>>,
+    /// Freestyle jobs
+    pub change_set: Option<JenkinsChangeSet>,
+}
+
+impl JenkinsBuildDetails {
+    fn get_parameters(&self) -> Vec<JenkinsBuildParam> {
+        self.actions.iter()
+            .find_map(|a| a.parameters.clone())
+            .unwrap_or_default()
+    }
+
+    fn get_test_counts(&self) -> Option<JenkinsTestCounts> {
+        self.actions.iter().find_map(|a| match (a.total_count, a.fail_count, a.skip_count) {
+            (Some(total), Some(failed), Some(skipped)) =>
+                Some(JenkinsTestCounts { total, failed, skipped }),
+            _ => None
+        })
+    }
+
+    fn get_commits(&self) -> Vec<JenkinsCommit> {
+        collect_commits(&self.change_sets, &self.change_set)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsJobBuilds {
+    pub last_build: Option<JenkinsBuildNumber>,
+    #[serde(default)]
+    pub builds: Vec<JenkinsBuildChanges>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+struct JenkinsBuildNumber {
+    pub number: u32,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsBuildChanges {
+    pub number: u32,
+    /// Pipeline jobs
+    pub change_sets: Option<Vec<JenkinsChangeSet>>,
+    /// Freestyle jobs
+    pub change_set: Option<JenkinsChangeSet>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsBuildDetailsAction {
+    pub parameters: Option<Vec<JenkinsBuildParam>>,
+    pub total_count: Option<u32>,
+    pub fail_count: Option<u32>,
+    pub skip_count: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsChangeSet {
+    pub items: Vec<JenkinsCommit>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsCommit {
+    pub commit_id: Option<String>,
+    pub msg: String,
+    pub author: JenkinsCommitAuthor,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsCommitAuthor {
+    pub full_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JenkinsTestCounts {
+    pub total: u32,
+    pub failed: u32,
+    pub skipped: u32,
+}
+
+impl Display for JenkinsBuildsDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "builds: #{} -> #{}", self.from, self.to)?;
+
+        writeln!(f, "result: {} -> {}",
+                 self.result.0.as_deref().unwrap_or("-"), self.result.1.as_deref().unwrap_or("-"))?;
+
+        writeln!(f, "duration: {} -> {} ({})", format_duration(self.duration.0),
+                 format_duration(self.duration.1), get_duration_delta(self.duration.0, self.duration.1))?;
+
+        match &self.tests {
+            (Some(from), Some(to)) => writeln!(f, "tests: total {} -> {} ({:+}), failed {} -> {} ({:+}), skipped {} -> {} ({:+})",
+                                               from.total, to.total, to.total as i64 - from.total as i64,
+                                               from.failed, to.failed, to.failed as i64 - from.failed as i64,
+                                               from.skipped, to.skipped, to.skipped as i64 - from.skipped as i64)?,
+            (None, None) => writeln!(f, "tests: no test results")?,
+            (from, to) => writeln!(f, "tests: total {} -> {}",
+                                   from.as_ref().map(|t| t.total.to_string()).unwrap_or("-".to_string()),
+                                   to.as_ref().map(|t| t.total.to_string()).unwrap_or("-".to_string()))?,
+        }
+
+        writeln!(f, "parameters:")?;
+
+        if self.parameters.is_empty() {
+            writeln!(f, "  no changes")?;
+        }
+
+        for change in &self.parameters {
+            match change {
+                ParameterChange::Added(name, value) => writeln!(f, "  + {name}: '{value}'")?,
+                ParameterChange::Removed(name, value) => writeln!(f, "  - {name}: '{value}'")?,
+                ParameterChange::Changed(name, old, new) => writeln!(f, "  ~ {name}: '{old}' -> '{new}'")?,
+            }
+        }
+
+        writeln!(f, "changes:")?;
+
+        if self.commits.is_empty() {
+            write!(f, "  no changes")?;
+        }
+
+        let lines = self.commits.iter().map(|c| {
+            let commit_id = c.commit_id.as_deref().unwrap_or("-");
+            let commit_id = commit_id.get(..8).unwrap_or(commit_id);
+            format!("  {commit_id} {}: {}", c.author.full_name, c.msg.trim())
+        }).collect::<Vec<String>>();
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+fn get_duration_delta(from: u64, to: u64) -> String {
+    if to >= from {
+        format!("+{}", format_duration(to - from))
+
+    } else {
+        format!("-{}", format_duration(from - to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jenkins::build::JenkinsBuildParam;
+    use crate::jenkins::diff::{get_intermediate_commits, get_parameter_changes, JenkinsBuildDetails, JenkinsJobBuilds,
+                               JenkinsTestCounts, ParameterChange};
+
+    #[test]
+    fn parameter_changes_should_be_detected() {
+        let from = vec![get_param("SCM_BRANCH", "dev"), get_param("CLEAR_CACHE", "false"),
+                        get_param("OLD", "1")];
+        let to = vec![get_param("SCM_BRANCH", "feature"), get_param("CLEAR_CACHE", "false"),
+                      get_param("NEW", "2")];
+
+        let changes = get_parameter_changes(&from, &to);
+
+        assert_eq!(vec![
+            ParameterChange::Changed("SCM_BRANCH".to_string(), "dev".to_string(), "feature".to_string()),
+            ParameterChange::Added("NEW".to_string(), "2".to_string()),
+            ParameterChange::Removed("OLD".to_string(), "1".to_string()),
+        ], changes);
+    }
+
+    #[test]
+    fn build_details_should_be_deserialized() {
+        let input = r#"{
+            "result": "FAILURE",
+            "duration": 12000,
+            "actions": [
+                {"_class": "hudson.model.ParametersAction", "parameters": [{"name": "SCM_BRANCH", "value": "dev"}]},
+                {},
+                {"_class": "hudson.tasks.junit.TestResultAction", "failCount": 2, "skipCount": 1, "totalCount": 40}
+            ],
+            "changeSets": [
+                {"items": [{"commitId": "a1b2c3d4e5f6", "msg": "fix", "author": {"fullName": "Dirk Gently"}}]}
+            ]
+        }"#;
+
+        let build = serde_json::from_str::<JenkinsBuildDetails>(input).unwrap();
+
+        assert_eq!(1, build.get_parameters().len());
+        assert_eq!(Some(JenkinsTestCounts { total: 40, failed: 2, skipped: 1 }), build.get_test_counts());
+        assert_eq!("Dirk Gently", build.get_commits().first().unwrap().author.full_name);
+    }
+
+    #[test]
+    fn intermediate_commits_should_be_collected_oldest_first() {
+        let input = r#"{
+            "_class": "org.jenkinsci.plugins.workflow.job.WorkflowJob",
+            "builds": [
+                {"number": 14, "changeSets": [{"items": [{"commitId": "e5", "msg": "newer", "author": {"fullName": "Dirk Gently"}}]}]},
+                {"number": 12, "changeSets": [{"items": [{"commitId": "c3", "msg": "second", "author": {"fullName": "Dirk Gently"}}]}]},
+                {"number": 11, "changeSet": {"items": [{"commitId": "b2", "msg": "first", "author": {"fullName": "Richard MacDuff"}}]}},
+                {"number": 10, "changeSets": [{"items": [{"commitId": "a1", "msg": "from", "author": {"fullName": "Dirk Gently"}}]}]}
+            ]
+        }"#;
+
+        let builds = serde_json::from_str::<JenkinsJobBuilds>(input).unwrap().builds;
+
+        let commits = get_intermediate_commits(builds, 10, 13);
+
+        assert_eq!(vec!["first", "second"], commits.iter().map(|c| c.msg.as_str()).collect::<Vec<&str>>());
+    }
+
+    fn get_param(name: &str, value: &str) -> JenkinsBuildParam {
+        JenkinsBuildParam {
+            name: name.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
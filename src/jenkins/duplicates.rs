@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::io::{IsTerminal, Write};
+
+use anyhow::Context;
+use log::{info, warn};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+use crate::jenkins::auth::JenkinsCrumbIssuer;
+use crate::jenkins::build::{get_next_build_params, JenkinsBuildParam};
+use crate::jenkins::get_job_url;
+
+const RECENT_BUILDS_COUNT: u32 = 10;
+
+/// What to do when job already has queued or running build with identical parameters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicateBuildPolicy {
+    /// Warn and ask user before triggering, see `confirm_duplicate_builds`. Build is skipped in non-interactive mode
+    Confirm,
+    /// Don't trigger build (`--if-idle`)
+    Skip,
+    /// Abort existing builds and trigger new one (`--cancel-running`)
+    CancelRunning,
+    /// Don't check (`--allow-duplicates`)
+    Allow,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateBuilds {
+    pub queue_item_id: Option<u64>,
+    /// Build urls
+    pub running: Vec<(u32, String)>,
+}
+
+impl DuplicateBuilds {
+    pub fn is_empty(&self) -> bool {
+        self.queue_item_id.is_none() && self.running.is_empty()
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsJobState {
+    #[serde(default)]
+    pub in_queue: bool,
+    pub queue_item: Option<JenkinsJobQueueItem>,
+    #[serde(default)]
+    pub builds: Vec<JenkinsJobBuild>,
+}
+
+/// Queue item params are `\nSCM_BRANCH=dev\nCLEAR_DOCKER_CACHE=false`
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsJobQueueItem {
+    pub id: u64,
+    pub params: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsJobBuild {
+    pub number: u32,
+    pub url: String,
+    pub building: bool,
+    #[serde(default)]
+    pub actions: Vec<JenkinsJobBuildAction>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsJobBuildAction {
+    pub parameters: Option<Vec<JenkinsBuildParam>>,
+}
+
+/// Queued and running builds of job with the same parameters.
+pub fn find_duplicate_builds(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                             job_name: &str, params: &[(String, String)]) -> anyhow::Result<DuplicateBuilds> {
+    info!("check queued and running builds of job '{job_name}'..");
+
+    let url = format!("{}/api/json?tree=inQueue,queueItem[id,params],builds[number,url,building,actions[parameters[name,value]]]{{0,{RECENT_BUILDS_COUNT}}}",
+                      get_job_url(jenkins_url, job_name));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get state of job '{job_name}'"))?;
+
+    let state = resp.json::<JenkinsJobState>()?;
+
+    Ok(get_duplicate_builds(&state, params))
+}
+
+fn get_duplicate_builds(state: &JenkinsJobState, params: &[(String, String)]) -> DuplicateBuilds {
+    let queue_item_id = state.queue_item.as_ref()
+        .filter(|_| state.in_queue)
+        .filter(|q| is_same_params(&parse_queue_item_params(q.params.as_deref().unwrap_or("")), params))
+        .map(|q| q.id);
+
+    let running = state.builds.iter()
+        .filter(|b| b.building)
+        .filter(|b| {
+            let build_params = b.actions.iter()
+                .find_map(|a| a.parameters.clone())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|p| (p.name, p.value))
+                .collect::<Vec<(String, String)>>();
+
+            is_same_params(&build_params, params)
+        })
+        .map(|b| (b.number, b.url.to_string()))
+        .collect();
+
+    DuplicateBuilds { queue_item_id, running }
+}
+
+fn parse_queue_item_params(params: &str) -> Vec<(String, String)> {
+    params.lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+fn is_same_params(left: &[(String, String)], right: &[(String, String)]) -> bool {
+    let mut left = left.to_vec();
+    let mut right = right.to_vec();
+
+    left.sort();
+    right.sort();
+
+    left == right
+}
+
+/// Cancel queue item and abort running builds.
+pub fn cancel_duplicate_builds(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                               crumb_issuer: &JenkinsCrumbIssuer, duplicates: &DuplicateBuilds) -> anyhow::Result<()> {
+    let mut urls: Vec<String> = vec![];
+
+    if let Some(id) = duplicates.queue_item_id {
+        urls.push(format!("{}/queue/cancelItem?id={id}", jenkins_url.trim_end_matches('/')));
+    }
+
+    duplicates.running.iter()
+        .for_each(|(_, url)| urls.push(format!("{}/stop", url.trim_end_matches('/'))));
+
+    for url in urls {
+        info!("cancel '{url}'..");
+
+        let resp = client.post(&url).basic_auth(username, Some(token))
+            .header(&crumb_issuer.crumb_request_field, &crumb_issuer.crumb)
+            .send()?;
+
+        info!("server response: {}", resp.status());
+
+        resp.error_for_status().context(format!("unable to cancel '{url}'"))?;
+    }
+
+    Ok(())
+}
+
+/// Duplicates are checked and confirmed before build is triggered, call it from main thread only,
+/// so prompts of parallel builds don't interleave. Returns `true` if build should be triggered.
+pub fn confirm_duplicate_builds(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                                job_name: &str, params: &HashMap<String, String>) -> anyhow::Result<bool> {
+    let build_params = get_next_build_params(client, jenkins_url, username, token, job_name, params)?;
+
+    let duplicates = find_duplicate_builds(client, jenkins_url, username, token, job_name, &build_params)?;
+
+    if duplicates.is_empty() {
+        return Ok(true)
+    }
+
+    eprintln!("warning: job '{job_name}' has {duplicates} with identical parameters");
+
+    Ok(confirm("trigger build anyway?"))
+}
+
+/// Ask user on terminal, always `false` in non-interactive mode.
+pub fn confirm(question: &str) -> bool {
+    if !io::stdin().is_terminal() {
+        warn!("non-interactive mode, '{question}' is declined");
+        eprintln!("{question} no (non-interactive mode)");
+        return false
+    }
+
+    eprint!("{question} [y/N] ");
+    io::stderr().flush().ok();
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).ok();
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+impl Display for DuplicateBuilds {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut items: Vec<String> = vec![];
+
+        if self.queue_item_id.is_some() {
+            items.push("queued build".to_string());
+        }
+
+        self.running.iter().for_each(|(number, _)| items.push(format!("running build #{number}")));
+
+        write!(f, "{}", items.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jenkins::duplicates::{DuplicateBuilds, get_duplicate_builds, JenkinsJobState};
+
+    #[test]
+    fn builds_with_identical_params_should_be_found() {
+        let input = r#"{
+            "inQueue": true,
+            "queueItem": {"id": 512, "params": "\nSCM_BRANCH=dev\nCLEAR_DOCKER_CACHE=false"},
+            "builds": [
+                {"number": 43, "url": "https://jenkins.company.com/job/api/43/", "building": true,
+                 "actions": [{"parameters": [{"name": "SCM_BRANCH", "value": "feature"}]}]},
+                {"number": 42, "url": "https://jenkins.company.com/job/api/42/", "building": true,
+                 "actions": [{}, {"parameters": [{"name": "CLEAR_DOCKER_CACHE", "value": false}, {"name": "SCM_BRANCH", "value": "dev"}]}]},
+                {"number": 41, "url": "https://jenkins.company.com/job/api/41/", "building": false,
+                 "actions": [{"parameters": [{"name": "SCM_BRANCH", "value": "dev"}, {"name": "CLEAR_DOCKER_CACHE", "value": false}]}]}
+            ]
+        }"#;
+
+        let state = serde_json::from_str::<JenkinsJobState>(input).unwrap();
+
+        let params = vec![("SCM_BRANCH".to_string(), "dev".to_string()),
+                          ("CLEAR_DOCKER_CACHE".to_string(), "false".to_string())];
+
+        let duplicates = get_duplicate_builds(&state, &params);
+
+        assert_eq!(DuplicateBuilds {
+            queue_item_id: Some(512),
+            running: vec![(42, "https://jenkins.company.com/job/api/42/".to_string())],
+        }, duplicates);
+
+        assert_eq!("queued build, running build #42", duplicates.to_string());
+    }
+
+    #[test]
+    fn idle_job_should_not_have_duplicates() {
+        let state = serde_json::from_str::<JenkinsJobState>(r#"{"inQueue": false, "builds": []}"#).unwrap();
+
+        assert!(get_duplicate_builds(&state, &[]).is_empty());
+    }
+}
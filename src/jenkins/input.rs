@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+use anyhow::{anyhow, Context};
+use log::info;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::jenkins::auth::get_crumb_issuer;
+use crate::jenkins::get_build_url;
+
+const BOOLEAN_PARAMETER_TYPE: &str = "BooleanParameterDefinition";
+
+/// Pipeline REST API: `<build>/wfapi/pendingInputActions`
+///
+/// ```json
+/// [
+///     {
+///         "id": "Deploy_approval",
+///         "proceedText": "Deploy",
+///         "message": "Deploy to production?",
+///         "inputs": [
+///             {
+///                 "type": "BooleanParameterDefinition",
+///                 "name": "FORCE",
+///                 "description": "",
+///                 "definition": {"defaultParameterValue": {"name": "FORCE", "value": false}}
+///             }
+///         ]
+///     }
+/// ]
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsInputAction {
+    pub id: String,
+    pub message: String,
+    pub proceed_text: Option<String>,
+    #[serde(default)]
+    pub inputs: Vec<JenkinsInputParam>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsInputParam {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub param_type: String,
+    pub description: Option<String>,
+    pub definition: Option<JenkinsInputParamDefinition>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsInputParamDefinition {
+    pub default_parameter_value: Option<JenkinsInputParamValue>,
+    pub choices: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsInputParamValue {
+    pub value: Option<Value>,
+}
+
+impl JenkinsInputParam {
+    fn get_default_value(&self) -> Option<Value> {
+        self.definition.as_ref()
+            .and_then(|d| d.default_parameter_value.as_ref())
+            .and_then(|v| v.value.clone())
+    }
+}
+
+pub fn get_pending_input_actions(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                                 job_name: &str, build: &str) -> anyhow::Result<Vec<JenkinsInputAction>> {
+    info!("get pending input actions of build '{build}' for job '{job_name}'..");
+
+    let url = format!("{}/wfapi/pendingInputActions", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get pending input actions of build '{build}' for job '{job_name}'"))?;
+
+    let actions = resp.json::<Vec<JenkinsInputAction>>()?;
+
+    info!("pending input actions: {:?}", actions);
+
+    Ok(actions)
+}
+
+/// Proceed input with values, default values are used for missing parameters.
+#[allow(clippy::too_many_arguments)]
+pub fn proceed_input(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                     job_name: &str, build: &str, action: &JenkinsInputAction,
+                     values: &HashMap<String, String>) -> anyhow::Result<()> {
+    info!("proceed input '{}' of build '{build}' for job '{job_name}'..", action.id);
+
+    if let Some(unknown) = values.keys().find(|k| !action.inputs.iter().any(|p| &p.name == *k)) {
+        return Err(anyhow!("input '{}' doesn't have parameter '{unknown}'", action.id))
+    }
+
+    let json = get_input_json(action, values)?;
+
+    let url = format!("{}/input/{}/proceed", get_build_url(jenkins_url, job_name, build),
+                      urlencoding::encode(&action.id));
+
+    post_input_form(client, jenkins_url, username, token, &url, vec![
+        ("json".to_string(), json.to_string()),
+        ("proceed".to_string(), action.proceed_text.clone().unwrap_or("Proceed".to_string())),
+    ])
+}
+
+pub fn abort_input(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                   job_name: &str, build: &str, action: &JenkinsInputAction) -> anyhow::Result<()> {
+    info!("abort input '{}' of build '{build}' for job '{job_name}'..", action.id);
+
+    let url = format!("{}/input/{}/abort", get_build_url(jenkins_url, job_name, build),
+                      urlencoding::encode(&action.id));
+
+    post_input_form(client, jenkins_url, username, token, &url, vec![])
+}
+
+fn post_input_form(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                   url: &str, mut form: Vec<(String, String)>) -> anyhow::Result<()> {
+    let crumb_issuer = get_crumb_issuer(client, jenkins_url, username, token)?;
+
+    form.push((crumb_issuer.crumb_request_field, crumb_issuer.crumb));
+
+    let resp = client.post(url).basic_auth(username, Some(token)).form(&form).send()?;
+
+    info!("server response: {}", resp.status());
+
+    resp.error_for_status().context(format!("unexpected server response for '{url}'"))?;
+
+    Ok(())
+}
+
+/// `{"parameter": [{"name": "FORCE", "value": true}]}`, boolean parameters are sent as json booleans.
+fn get_input_json(action: &JenkinsInputAction, values: &HashMap<String, String>) -> anyhow::Result<Value> {
+    let parameters = action.inputs.iter().map(|p| {
+        let is_boolean = p.param_type == BOOLEAN_PARAMETER_TYPE;
+
+        let value = match (values.get(&p.name), p.get_default_value()) {
+            (Some(value), _) if is_boolean => Value::Bool(parse_bool(&p.name, value)?),
+            (Some(value), _) => Value::String(value.to_string()),
+            (None, Some(default_value)) => default_value,
+            (None, None) if is_boolean => Value::Bool(false),
+            (None, None) => Value::String("".to_string()),
+        };
+
+        Ok(json!({"name": p.name, "value": value}))
+    }).collect::<anyhow::Result<Vec<Value>>>()?;
+
+    Ok(json!({"parameter": parameters}))
+}
+
+/// Only `true` and `false`, so typo doesn't turn into `false` silently.
+fn parse_bool(name: &str, value: &str) -> anyhow::Result<bool> {
+    match value {
+        "true" => Ok(true),
+        "false" => Ok(false),
+        _ => Err(anyhow!("invalid value '{value}' of boolean parameter '{name}', expected 'true' or 'false'"))
+    }
+}
+
+impl Display for JenkinsInputAction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.id, self.message)?;
+
+        for param in &self.inputs {
+            write!(f, "\n  {} ({})", param.name, param.param_type.trim_end_matches("ParameterDefinition"))?;
+
+            if let Some(default_value) = param.get_default_value() {
+                write!(f, ", default: {default_value}")?;
+            }
+
+            if let Some(choices) = param.definition.as_ref().and_then(|d| d.choices.as_ref()) {
+                write!(f, ", choices: {}", choices.join(" | "))?;
+            }
+
+            if let Some(description) = param.description.as_ref().filter(|d| !d.is_empty()) {
+                write!(f, " - {description}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use crate::jenkins::input::{get_input_json, JenkinsInputAction};
+
+    #[test]
+    fn input_json_should_use_values_and_defaults() {
+        let input = r#"[{
+            "id": "Deploy_approval",
+            "proceedText": "Deploy",
+            "message": "Deploy to production?",
+            "inputs": [
+                {"type": "BooleanParameterDefinition", "name": "FORCE", "description": "",
+                 "definition": {"defaultParameterValue": {"name": "FORCE", "value": false}}},
+                {"type": "ChoiceParameterDefinition", "name": "REGION", "description": "",
+                 "definition": {"defaultParameterValue": {"name": "REGION", "value": "eu"}, "choices": ["eu", "us"]}}
+            ]
+        }]"#;
+
+        let actions = serde_json::from_str::<Vec<JenkinsInputAction>>(input).unwrap();
+        let action = actions.first().unwrap();
+
+        let values = HashMap::from([("FORCE".to_string(), "true".to_string())]);
+
+        assert_eq!(json!({"parameter": [{"name": "FORCE", "value": true}, {"name": "REGION", "value": "eu"}]}),
+                   get_input_json(action, &values).unwrap());
+
+        for invalid in ["yes", "True", "1", ""] {
+            let values = HashMap::from([("FORCE".to_string(), invalid.to_string())]);
+
+            assert!(get_input_json(action, &values).is_err());
+        }
+    }
+
+    #[test]
+    fn boolean_should_be_detected_by_parameter_type() {
+        let input = r#"[{
+            "id": "Deploy_approval",
+            "proceedText": "Deploy",
+            "message": "Deploy to production?",
+            "inputs": [
+                {"type": "BooleanParameterDefinition", "name": "FORCE", "description": ""},
+                {"type": "BooleanParameterDefinition", "name": "NOTIFY", "description": ""},
+                {"type": "StringParameterDefinition", "name": "TAG", "description": "",
+                 "definition": {"defaultParameterValue": {"name": "TAG", "value": true}}}
+            ]
+        }]"#;
+
+        let actions = serde_json::from_str::<Vec<JenkinsInputAction>>(input).unwrap();
+        let action = actions.first().unwrap();
+
+        let values = HashMap::from([
+            ("FORCE".to_string(), "true".to_string()),
+            ("TAG".to_string(), "true".to_string()),
+        ]);
+
+        assert_eq!(json!({"parameter": [{"name": "FORCE", "value": true}, {"name": "NOTIFY", "value": false},
+                                        {"name": "TAG", "value": "true"}]}),
+                   get_input_json(action, &values).unwrap());
+    }
+}
@@ -1,7 +1,16 @@
+use std::time::Duration;
+
+use anyhow::Context;
 use log::info;
 use reqwest::blocking::Client;
 use serde::{Deserialize, Serialize};
-use crate::cache::{load_job_list_from_cache, save_job_list_in_cache};
+use crate::cache::{CacheMode, load_job_list_from_cache, save_job_list_in_cache};
+use crate::config::CacheConfig;
+use crate::history::JobUsage;
+use crate::jenkins::get_job_url;
+use crate::jenkins::views::get_view_url;
+
+const BUILDING_COLOR_SUFFIX: &str = "_anime";
 
 #[derive(Serialize,Deserialize)]
 pub struct JenkinsApiResponse {
@@ -14,13 +23,213 @@ pub struct JenkinsJob {
     pub url: String
 }
 
-pub fn get_jenkins_job_list(client: &Client, jenkins_url: &str,
-                            username: &str, token: &str) -> anyhow::Result<Vec<JenkinsJob>> {
+/// Job with status, isn't cached since it changes with every build.
+///
+/// `color` is `blue`, `red`, `yellow`, `aborted`, `notbuilt`, `disabled`, with `_anime` suffix while building.
+#[derive(Deserialize,Clone,Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsJobStatus {
+    pub name: String,
+    pub url: String,
+    pub color: Option<String>,
+    pub last_build: Option<JenkinsJobLastBuild>,
+}
+
+#[derive(Deserialize,Clone,Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsJobLastBuild {
+    pub number: u32,
+    /// Start time, unix millis
+    pub timestamp: u64,
+    pub duration: u64,
+}
+
+#[derive(Deserialize)]
+struct JenkinsJobStatusResponse {
+    pub jobs: Vec<JenkinsJobStatus>
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum JobStatus {
+    Failed,
+    Unstable,
+    Success,
+    Disabled,
+    Running,
+    NeverBuilt,
+}
+
+impl JenkinsJobStatus {
+    pub fn has_status(&self, status: JobStatus) -> bool {
+        let color = self.color.as_deref().unwrap_or("");
+        let base_color = color.trim_end_matches(BUILDING_COLOR_SUFFIX);
+
+        match status {
+            JobStatus::Failed => base_color == "red",
+            JobStatus::Unstable => base_color == "yellow",
+            JobStatus::Success => base_color == "blue",
+            JobStatus::Disabled => base_color == "disabled",
+            JobStatus::Running => color.ends_with(BUILDING_COLOR_SUFFIX),
+            JobStatus::NeverBuilt => self.last_build.is_none(),
+        }
+    }
+}
+
+/// Filters for `list`, all present filters should match.
+#[derive(Debug,Clone,Default)]
+pub struct JobStatusFilter {
+    /// Any of statuses
+    pub statuses: Vec<JobStatus>,
+    pub built_since: Option<Duration>,
+    pub not_built_since: Option<Duration>,
+}
+
+impl JobStatusFilter {
+    pub fn is_empty(&self) -> bool {
+        self.statuses.is_empty() && self.built_since.is_none() && self.not_built_since.is_none()
+    }
+
+    /// `now` is unix millis.
+    pub fn matches(&self, job: &JenkinsJobStatus, now: u64) -> bool {
+        let last_build_age = job.last_build.as_ref()
+            .map(|b| Duration::from_millis(now.saturating_sub(b.timestamp)));
+
+        (self.statuses.is_empty() || self.statuses.iter().any(|s| job.has_status(*s))) &&
+            self.built_since.is_none_or(|d| last_build_age.is_some_and(|age| age <= d)) &&
+            self.not_built_since.is_none_or(|d| last_build_age.is_none_or(|age| age > d))
+    }
+}
+
+/// Jobs of view if present, all jobs otherwise.
+pub fn get_jenkins_job_statuses(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                                view: Option<&str>) -> anyhow::Result<Vec<JenkinsJobStatus>> {
+    info!("get job statuses from jenkins '{jenkins_url}', username '{username}', view {:?}", view);
+
+    let base_url = match view {
+        Some(view) => get_view_url(jenkins_url, view),
+        None => jenkins_url.trim_end_matches('/').to_string()
+    };
+
+    let url = format!("{base_url}/api/json?tree=jobs[name,url,color,lastBuild[number,timestamp,duration]]");
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context("unable to get job statuses")?;
+
+    let resp = resp.json::<JenkinsJobStatusResponse>()?;
+
+    info!("job statuses: {:?}", resp.jobs);
+
+    Ok(resp.jobs)
+}
+
+/// Status of single job, folders are supported. Name is the full one, as passed.
+pub fn get_jenkins_job_status(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                              job_name: &str) -> anyhow::Result<JenkinsJobStatus> {
+    info!("get job status '{job_name}' from jenkins '{jenkins_url}', username '{username}'");
+
+    let url = format!("{}/api/json?tree=name,url,color,lastBuild[number,timestamp,duration]",
+                      get_job_url(jenkins_url, job_name));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get status of job '{job_name}'"))?;
+
+    let mut job = resp.json::<JenkinsJobStatus>()?;
+    job.name = job_name.to_string();
+
+    info!("job status: {:?}", job);
+
+    Ok(job)
+}
+
+/// Favorites are full job names, jobs inside folders aren't present in job list of server.
+pub fn get_favorite_job_list(jenkins_url: &str, favorites: &[String]) -> Vec<JenkinsJob> {
+    favorites.iter()
+        .map(|name| JenkinsJob { name: name.to_string(), url: get_job_url(jenkins_url, name) })
+        .collect()
+}
+
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub enum JobSort {
+    Name,
+    /// Recently built first
+    LastBuild,
+    /// Longest first
+    Duration,
+    /// Failed first, then unstable, aborted, successful, never built and disabled
+    Status,
+    /// Most often built with jencli first
+    RecentUse,
+}
+
+impl JobSort {
+    /// Status, last build and duration are available only in `JenkinsJobStatus`
+    pub fn requires_status(&self) -> bool {
+        matches!(self, JobSort::LastBuild | JobSort::Duration | JobSort::Status)
+    }
+}
+
+/// Sort by status, last build or duration, order is kept for other sorts.
+pub fn sort_job_statuses(jobs: &mut [JenkinsJobStatus], sort: JobSort) {
+    match sort {
+        JobSort::LastBuild => jobs.sort_by_key(|j| std::cmp::Reverse(j.last_build.as_ref().map(|b| b.timestamp))),
+        JobSort::Duration => jobs.sort_by_key(|j| std::cmp::Reverse(j.last_build.as_ref().map(|b| b.duration))),
+        JobSort::Status => jobs.sort_by_key(get_status_rank),
+        JobSort::Name | JobSort::RecentUse => {}
+    }
+}
+
+/// Sort by name or recent use, order is kept for other sorts. Jobs never built with jencli keep their order.
+pub fn sort_job_list(jobs: &mut [JenkinsJob], sort: JobSort, usage: &[JobUsage]) {
+    match sort {
+        JobSort::Name => jobs.sort_by_key(|j| j.name.to_lowercase()),
+        JobSort::RecentUse => jobs.sort_by_key(|j| std::cmp::Reverse(get_job_usage(&j.name, usage))),
+        JobSort::LastBuild | JobSort::Duration | JobSort::Status => {}
+    }
+}
+
+/// Builds and last build of job, for folder - of all jobs inside it, since history keeps full job names.
+fn get_job_usage(job_name: &str, usage: &[JobUsage]) -> Option<(u32, i64)> {
+    let folder_prefix = format!("{job_name}/");
+
+    usage.iter()
+        .filter(|u| u.job_name == job_name || u.job_name.starts_with(&folder_prefix))
+        .fold(None, |total, u| match total {
+            Some((builds, last_build)) => Some((builds + u.builds, std::cmp::max(last_build, u.last_build))),
+            None => Some((u.builds, u.last_build))
+        })
+}
+
+fn get_status_rank(job: &JenkinsJobStatus) -> u8 {
+    match job.color.as_deref().unwrap_or("").trim_end_matches(BUILDING_COLOR_SUFFIX) {
+        "red" => 0,
+        "yellow" => 1,
+        "aborted" => 2,
+        "blue" => 3,
+        "notbuilt" => 4,
+        "disabled" => 5,
+        _ => 6
+    }
+}
+
+/// Cached job list is used until it's older than cache ttl, see `CacheMode` for `--refresh` and `--no-cache`.
+pub fn get_jenkins_job_list(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                            cache: &CacheConfig) -> anyhow::Result<Vec<JenkinsJob>> {
     info!("get job list from jenkins '{jenkins_url}', username '{username}'");
 
-    let job_list_from_cache = load_job_list_from_cache()?;
+    let job_list_from_cache = match cache.mode {
+        CacheMode::Use => load_job_list_from_cache(jenkins_url, username, cache.ttl)?,
+        CacheMode::Refresh | CacheMode::Bypass => None
+    };
+
+    if let Some(job_list_from_cache) = job_list_from_cache {
+        info!("jobs: (from cache)");
+        info!("{:?}", job_list_from_cache);
 
-    if job_list_from_cache.is_empty() {
+        Ok(job_list_from_cache)
+
+    } else {
         let url = format!("{jenkins_url}/api/json");
 
         let resp = client.get(&url).basic_auth(username, Some(token)).send()?;
@@ -30,22 +239,142 @@ pub fn get_jenkins_job_list(client: &Client, jenkins_url: &str,
         info!("jobs:");
         info!("{:?}", resp.jobs);
 
-        save_job_list_in_cache(&resp.jobs)?;
+        if cache.mode != CacheMode::Bypass {
+            save_job_list_in_cache(jenkins_url, username, &resp.jobs)?;
+        }
 
-        Ok(resp.jobs.clone())
+        Ok(resp.jobs)
+    }
+}
 
-    } else {
-        info!("jobs: (from cache)");
-        info!("{:?}", job_list_from_cache);
+/// Jobs without excluded ones, filtered by mask (case-insensitive) if present.
+pub fn filter_job_list(job_list: Vec<JenkinsJob>, exclude: &[String],
+                       mask: Option<&String>) -> Vec<JenkinsJob> {
+    let job_list = job_list.into_iter()
+        .filter(|j| !is_job_excluded(&j.name, exclude));
 
-        Ok(job_list_from_cache)
+    match mask {
+        Some(mask) => {
+            let mask = mask.to_lowercase();
+            job_list.filter(|j| j.name.to_lowercase().contains(&mask)).collect()
+        }
+        None => job_list.collect()
     }
 }
 
+/// Job name contains any of `list.exclude` entries.
+pub fn is_job_excluded(job_name: &str, exclude: &[String]) -> bool {
+    exclude.iter().any(|e| job_name.contains(e))
+}
+
 #[cfg(test)]
 mod tests {
     use reqwest::blocking::ClientBuilder;
-    use crate::jenkins::list::get_jenkins_job_list;
+    use std::time::Duration;
+
+    use crate::cache::CacheMode;
+    use crate::config::CacheConfig;
+    use crate::history::JobUsage;
+    use crate::jenkins::list::{filter_job_list, get_favorite_job_list, get_jenkins_job_list, is_job_excluded, JenkinsJob,
+                               JenkinsJobStatus, JobSort, JobStatus, JobStatusFilter, sort_job_list, sort_job_statuses};
+
+    const DAY_MILLIS: u64 = 24 * 60 * 60 * 1000;
+
+    #[test]
+    fn job_list_should_be_filtered_by_exclude_and_mask() {
+        let job_list = ["PROD-api-deploy", "api-deploy", "web-deploy", "api-tests"].iter()
+            .map(|name| JenkinsJob { name: name.to_string(), url: format!("https://jenkins.company.com/job/{name}") })
+            .collect::<Vec<JenkinsJob>>();
+
+        let results = filter_job_list(job_list, &["PROD-".to_string()], Some(&"API".to_string()));
+
+        assert_eq!(vec!["api-deploy", "api-tests"],
+                   results.iter().map(|j| j.name.as_str()).collect::<Vec<&str>>());
+
+        assert!(is_job_excluded("team/PROD-api-deploy", &["PROD-".to_string()]));
+        assert!(!is_job_excluded("team/api-deploy", &["PROD-".to_string()]));
+    }
+
+    #[test]
+    fn job_statuses_should_be_filtered() {
+        let now = 100 * DAY_MILLIS;
+
+        let jobs = serde_json::from_str::<Vec<JenkinsJobStatus>>(&format!(r#"[
+            {{"name": "api-build", "url": "", "color": "red", "lastBuild": {{"number": 10, "timestamp": {}, "duration": 1000}}}},
+            {{"name": "api-deploy", "url": "", "color": "blue_anime", "lastBuild": {{"number": 5, "timestamp": {}, "duration": 1000}}}},
+            {{"name": "old-tool", "url": "", "color": "disabled", "lastBuild": {{"number": 1, "timestamp": 0, "duration": 1000}}}},
+            {{"name": "new-job", "url": "", "color": "notbuilt", "lastBuild": null}}
+        ]"#, now - DAY_MILLIS, now - 10 * DAY_MILLIS)).unwrap();
+
+        let get_names = |filter: JobStatusFilter| jobs.iter()
+            .filter(|j| filter.matches(j, now))
+            .map(|j| j.name.as_str())
+            .collect::<Vec<&str>>();
+
+        assert_eq!(vec!["api-build"], get_names(JobStatusFilter { statuses: vec![JobStatus::Failed], ..Default::default() }));
+        assert_eq!(vec!["api-deploy"], get_names(JobStatusFilter { statuses: vec![JobStatus::Running, JobStatus::Unstable], ..Default::default() }));
+        assert_eq!(vec!["new-job"], get_names(JobStatusFilter { statuses: vec![JobStatus::NeverBuilt], ..Default::default() }));
+
+        assert_eq!(vec!["api-build"], get_names(JobStatusFilter { built_since: Some(Duration::from_millis(7 * DAY_MILLIS)), ..Default::default() }));
+        assert_eq!(vec!["old-tool", "new-job"], get_names(JobStatusFilter { not_built_since: Some(Duration::from_millis(90 * DAY_MILLIS)), ..Default::default() }));
+    }
+
+    #[test]
+    fn jobs_should_be_sorted() {
+        let mut jobs = serde_json::from_str::<Vec<JenkinsJobStatus>>(r#"[
+            {"name": "web-deploy", "url": "", "color": "blue", "lastBuild": {"number": 10, "timestamp": 300, "duration": 1000}},
+            {"name": "api-build", "url": "", "color": "notbuilt", "lastBuild": null},
+            {"name": "Api-deploy", "url": "", "color": "red_anime", "lastBuild": {"number": 5, "timestamp": 500, "duration": 5000}}
+        ]"#).unwrap();
+
+        let get_names = |jobs: &[JenkinsJobStatus]| jobs.iter().map(|j| j.name.to_string()).collect::<Vec<String>>();
+
+        sort_job_statuses(&mut jobs, JobSort::Status);
+        assert_eq!(vec!["Api-deploy", "web-deploy", "api-build"], get_names(&jobs));
+
+        sort_job_statuses(&mut jobs, JobSort::LastBuild);
+        assert_eq!(vec!["Api-deploy", "web-deploy", "api-build"], get_names(&jobs));
+
+        sort_job_statuses(&mut jobs, JobSort::Duration);
+        assert_eq!(vec!["Api-deploy", "web-deploy", "api-build"], get_names(&jobs));
+
+        let mut job_list = jobs.into_iter()
+            .map(|j| JenkinsJob { name: j.name, url: j.url })
+            .collect::<Vec<JenkinsJob>>();
+
+        sort_job_list(&mut job_list, JobSort::Name, &[]);
+        assert_eq!(vec!["api-build", "Api-deploy", "web-deploy"],
+                   job_list.iter().map(|j| j.name.as_str()).collect::<Vec<&str>>());
+
+        let usage = vec![
+            JobUsage { job_name: "web-deploy".to_string(), builds: 1, last_build: 100 },
+            JobUsage { job_name: "Api-deploy".to_string(), builds: 5, last_build: 50 },
+        ];
+
+        sort_job_list(&mut job_list, JobSort::RecentUse, &usage);
+        assert_eq!(vec!["Api-deploy", "web-deploy", "api-build"],
+                   job_list.iter().map(|j| j.name.as_str()).collect::<Vec<&str>>());
+
+        let usage = vec![
+            JobUsage { job_name: "api-build/main".to_string(), builds: 4, last_build: 100 },
+            JobUsage { job_name: "api-build/develop".to_string(), builds: 3, last_build: 200 },
+            JobUsage { job_name: "Api-deploy".to_string(), builds: 5, last_build: 50 },
+        ];
+
+        sort_job_list(&mut job_list, JobSort::RecentUse, &usage);
+        assert_eq!(vec!["api-build", "Api-deploy", "web-deploy"],
+                   job_list.iter().map(|j| j.name.as_str()).collect::<Vec<&str>>());
+    }
+
+    #[test]
+    fn favorite_job_list_should_include_jobs_inside_folders() {
+        let favorites = vec!["web-deploy".to_string(), "team/api".to_string()];
+
+        let job_list = get_favorite_job_list("https://jenkins.company.com/", &favorites);
+
+        assert_eq!(vec!["web-deploy", "team/api"], job_list.iter().map(|j| j.name.as_str()).collect::<Vec<&str>>());
+        assert_eq!("https://jenkins.company.com/job/team/job/api", job_list[1].url);
+    }
 
     #[ignore]
     #[test]
@@ -56,7 +385,9 @@ mod tests {
 
         let client = ClientBuilder::new().build().unwrap();
 
-        let jobs = get_jenkins_job_list(&client, &jenkins_url, &username, &token).unwrap();
+        let cache = CacheConfig { mode: CacheMode::Bypass, ..CacheConfig::default() };
+
+        let jobs = get_jenkins_job_list(&client, jenkins_url, username, token, &cache).unwrap();
 
         assert!(!jobs.is_empty())
     }
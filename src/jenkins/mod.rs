@@ -1,4 +1,275 @@
 pub mod list;
 pub mod build;
 pub mod auth;
+pub mod diff;
+pub mod artifacts;
+pub mod wait;
+pub mod test_report;
+pub mod stages;
+pub mod input;
+pub mod replay;
+pub mod restart;
+pub mod bulk;
+pub mod duplicates;
+pub mod describe;
+pub mod retention;
+pub mod views;
+pub mod resolve;
 
+use std::time::Duration;
+
+use anyhow::anyhow;
+
+const PERMALINKS: [&str; 6] = ["lastBuild", "lastSuccessfulBuild", "lastFailedBuild", "lastStableBuild",
+                               "lastUnstableBuild", "lastCompletedBuild"];
+
+/// Job or build url pasted from browser, see `parse_jenkins_url`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JenkinsUrl {
+    pub server: String,
+    /// Full name, folders are separated with `/`
+    pub job_name: String,
+    /// Build number or permalink
+    pub build: Option<String>,
+}
+
+impl JenkinsUrl {
+    /// Scheme and host are case-insensitive, trailing slash is ignored.
+    pub fn is_same_server(&self, jenkins_url: &str) -> bool {
+        self.server.trim_end_matches('/').to_lowercase() == jenkins_url.trim_end_matches('/').to_lowercase()
+    }
+}
+
+pub fn is_url(value: &str) -> bool {
+    value.starts_with("http://") || value.starts_with("https://")
+}
+
+/// `https://jenkins.company.com/view/team/job/team/job/api/123/console` ->
+/// server `https://jenkins.company.com`, job `team/api`, build `123`
+pub fn parse_jenkins_url(url: &str) -> anyhow::Result<JenkinsUrl> {
+    let path_start = url.find("://").map(|i| i + 3)
+        .and_then(|i| url[i..].find('/').map(|j| i + j))
+        .unwrap_or(url.len());
+
+    let segments = url[path_start..].split(['?', '#']).next().unwrap_or("")
+        .split('/')
+        .collect::<Vec<&str>>();
+
+    // Server might have prefix, e.g. `https://company.com/jenkins`
+    let jobs_start = segments.iter().position(|s| *s == "job" || *s == "view")
+        .ok_or(anyhow!("url '{url}' doesn't point to jenkins job"))?;
+
+    let server = format!("{}{}", &url[..path_start], segments[..jobs_start].join("/"));
+
+    let mut job_names: Vec<String> = vec![];
+    let mut build: Option<String> = None;
+
+    let mut rest = segments[jobs_start..].iter().filter(|s| !s.is_empty());
+
+    while let Some(segment) = rest.next() {
+        match *segment {
+            "job" | "view" => {
+                let name = rest.next().ok_or(anyhow!("url '{url}' doesn't point to jenkins job"))?;
+                let name = urlencoding::decode(name).map(|n| n.to_string()).unwrap_or(name.to_string());
+
+                if *segment == "job" {
+                    job_names.push(name);
+                }
+            }
+            _ if !job_names.is_empty() && (segment.parse::<u32>().is_ok() || PERMALINKS.contains(segment)) => {
+                build = Some(segment.to_string());
+                break
+            }
+            _ => break
+        }
+    }
+
+    if job_names.is_empty() {
+        return Err(anyhow!("url '{url}' doesn't point to jenkins job"))
+    }
+
+    Ok(JenkinsUrl { server, job_name: job_names.join("/"), build })
+}
+
+/// Build argument first, then build of job url, then `default` permalink.
+pub fn select_build(build: Option<&str>, job: &str, default: Option<&str>) -> anyhow::Result<String> {
+    if let Some(build) = build {
+        return Ok(build.to_string())
+    }
+
+    let url_build = match is_url(job) {
+        true => parse_jenkins_url(job)?.build,
+        false => None
+    };
+
+    url_build.or(default.map(|d| d.to_string()))
+        .ok_or(anyhow!("build is required, pass build number or build url"))
+}
+
+/// Url for job by full name, folders are supported:
+///
+/// `team/api` -> `https://jenkins.company.com/job/team/job/api`
+pub fn get_job_url(jenkins_url: &str, job_name: &str) -> String {
+    let path = job_name.split('/')
+        .filter(|part| !part.is_empty())
+        .map(|part| format!("job/{}", urlencoding::encode(part)))
+        .collect::<Vec<String>>()
+        .join("/");
+
+    format!("{}/{path}", jenkins_url.trim_end_matches('/'))
+}
+
+/// Build reference: number or permalink (`lastBuild`, `lastFailedBuild`, etc.)
+pub fn get_build_url(jenkins_url: &str, job_name: &str, build: &str) -> String {
+    format!("{}/{build}", get_job_url(jenkins_url, job_name))
+}
+
+/// Jenkins durations are in milliseconds: `312000` -> `5m 12s`
+pub fn format_duration(millis: u64) -> String {
+    let seconds = millis / 1000;
+
+    let hours = seconds / 3600;
+    let minutes = seconds % 3600 / 60;
+    let seconds = seconds % 60;
+
+    if hours > 0 {
+        format!("{hours}h {minutes}m {seconds}s")
+
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// `512 B`, `1.5 KB`, `10.0 MB`, `2.0 GB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{bytes} B")
+
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// `90` (seconds), `30s`, `15m`, `2h`, `7d`
+pub fn parse_duration(value: &str) -> anyhow::Result<Duration> {
+    let value = value.trim();
+
+    let (number, multiplier) = match value.char_indices().last() {
+        Some((index, 's')) => (&value[..index], 1),
+        Some((index, 'm')) => (&value[..index], 60),
+        Some((index, 'h')) => (&value[..index], 3600),
+        Some((index, 'd')) => (&value[..index], 86400),
+        _ => (value, 1)
+    };
+
+    let number = number.trim().parse::<u64>()
+        .map_err(|_| anyhow!("invalid duration '{value}', expected format like 30s, 15m, 2h, 7d"))?;
+
+    Ok(Duration::from_secs(number * multiplier))
+}
+
+pub fn unescape_html(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::jenkins::{format_duration, format_size, get_build_url, get_job_url, JenkinsUrl, parse_duration, parse_jenkins_url,
+                         select_build};
+
+    #[test]
+    fn jenkins_urls_should_be_parsed() {
+        assert_eq!(JenkinsUrl {
+            server: "https://jenkins.company.com".to_string(),
+            job_name: "team/api".to_string(),
+            build: Some("123".to_string()),
+        }, parse_jenkins_url("https://jenkins.company.com/job/team/job/api/123/console").unwrap());
+
+        let url = parse_jenkins_url("https://company.com/jenkins/view/backend/job/api%20build/lastBuild/").unwrap();
+
+        assert_eq!("https://company.com/jenkins", url.server);
+        assert_eq!("api build", url.job_name);
+        assert_eq!(Some("lastBuild".to_string()), url.build);
+        assert!(url.is_same_server("HTTPS://company.com/jenkins/"));
+
+        let url = parse_jenkins_url("https://jenkins.company.com/job/api/changes?foo=1").unwrap();
+        assert_eq!(("api", None), (url.job_name.as_str(), url.build));
+
+        assert!(parse_jenkins_url("https://jenkins.company.com/view/backend/").is_err());
+        assert!(parse_jenkins_url("https://jenkins.company.com").is_err());
+    }
+
+    #[test]
+    fn build_of_url_should_take_precedence_over_default() {
+        let url = "https://jenkins.company.com/job/team/job/api/123/console";
+
+        assert_eq!("123", select_build(None, url, Some("lastBuild")).unwrap());
+        assert_eq!("120", select_build(Some("120"), url, Some("lastBuild")).unwrap());
+        assert_eq!("lastBuild", select_build(None, "https://jenkins.company.com/job/api/", Some("lastBuild")).unwrap());
+        assert_eq!("lastSuccessfulBuild", select_build(None, "team/api", Some("lastSuccessfulBuild")).unwrap());
+        assert!(select_build(None, "team/api", None).is_err());
+    }
+
+    #[test]
+    fn job_url_should_support_folders() {
+        assert_eq!("https://jenkins.company.com/job/team/job/api",
+                   get_job_url("https://jenkins.company.com/", "team/api"));
+    }
+
+    #[test]
+    fn job_name_parts_should_be_encoded() {
+        assert_eq!("https://jenkins.company.com/job/my%20job",
+                   get_job_url("https://jenkins.company.com", "my job"));
+    }
+
+    #[test]
+    fn build_url_should_be_returned() {
+        assert_eq!("https://jenkins.company.com/job/api/lastBuild",
+                   get_build_url("https://jenkins.company.com", "api", "lastBuild"));
+    }
+
+    #[test]
+    fn duration_should_be_formatted() {
+        assert_eq!("0s", format_duration(450));
+        assert_eq!("5m 12s", format_duration(312_000));
+        assert_eq!("1h 0m 5s", format_duration(3_605_000));
+    }
+
+    #[test]
+    fn size_should_be_formatted() {
+        assert_eq!("512 B", format_size(512));
+        assert_eq!("1.5 KB", format_size(1536));
+        assert_eq!("10.0 MB", format_size(10 * 1024 * 1024));
+        assert_eq!("2.0 GB", format_size(2 * 1024 * 1024 * 1024));
+    }
+
+    #[test]
+    fn duration_should_be_parsed() {
+        assert_eq!(Duration::from_secs(90), parse_duration("90").unwrap());
+        assert_eq!(Duration::from_secs(30), parse_duration("30s").unwrap());
+        assert_eq!(Duration::from_secs(15 * 60), parse_duration("15m").unwrap());
+        assert_eq!(Duration::from_secs(7 * 86400), parse_duration("7d").unwrap());
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Context};
+use log::info;
+use reqwest::blocking::Client;
+use serde_json::{Map, Value};
+
+use crate::jenkins::auth::get_crumb_issuer;
+use crate::jenkins::{get_build_url, unescape_html};
+
+const MAIN_SCRIPT_FIELD: &str = "mainScript";
+
+const FIELD_NAME_PREFIX: &str = "name=\"_.";
+
+/// Scripts of build available for replay: main script (Jenkinsfile) and
+/// scripts loaded with `load` step or from shared libraries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JenkinsReplayScripts {
+    pub main_script: String,
+    /// Form field name -> script. Jenkins replaces dots with underscores in field names.
+    pub loaded_scripts: HashMap<String, String>,
+}
+
+impl JenkinsReplayScripts {
+    /// Replace loaded script by name, dotted (`com.company.Utils`) and form field names are supported.
+    pub fn set_loaded_script(&mut self, name: &str, script: String) -> anyhow::Result<()> {
+        let field_name = name.replace('.', "_");
+
+        match self.loaded_scripts.get_mut(&field_name) {
+            Some(value) => {
+                *value = script;
+                Ok(())
+            }
+            None => Err(anyhow!("loaded script '{name}' not found, available scripts: {}",
+                                self.loaded_scripts.keys().cloned().collect::<Vec<String>>().join(", ")))
+        }
+    }
+}
+
+/// Replay action doesn't have REST API, scripts are taken from replay form.
+pub fn get_replay_scripts(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                          job_name: &str, build: &str) -> anyhow::Result<JenkinsReplayScripts> {
+    info!("get replay scripts of build '{build}' for job '{job_name}'..");
+
+    let url = format!("{}/replay/", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get replay form of build '{build}' for job '{job_name}', is it pipeline job?"))?;
+
+    let html = resp.text()?;
+
+    parse_replay_form(&html)
+}
+
+pub fn replay_build(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                    job_name: &str, build: &str, scripts: &JenkinsReplayScripts) -> anyhow::Result<()> {
+    info!("replay build '{build}' for job '{job_name}'..");
+
+    let crumb_issuer = get_crumb_issuer(client, jenkins_url, username, token)?;
+
+    let mut json = Map::new();
+    json.insert(MAIN_SCRIPT_FIELD.to_string(), Value::String(scripts.main_script.to_string()));
+
+    scripts.loaded_scripts.iter().for_each(|(name, script)| {
+        json.insert(name.to_string(), Value::String(script.to_string()));
+    });
+
+    let form = vec![
+        ("json".to_string(), Value::Object(json).to_string()),
+        (crumb_issuer.crumb_request_field, crumb_issuer.crumb),
+    ];
+
+    let url = format!("{}/replay/run", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.post(&url).basic_auth(username, Some(token)).form(&form).send()?;
+
+    info!("server response: {}", resp.status());
+
+    resp.error_for_status().context(format!("unable to replay build '{build}' for job '{job_name}'"))?;
+
+    Ok(())
+}
+
+/// Textareas `name="_.mainScript"` and `name="_.<loaded script>"`.
+fn parse_replay_form(html: &str) -> anyhow::Result<JenkinsReplayScripts> {
+    let mut main_script: Option<String> = None;
+    let mut loaded_scripts: HashMap<String, String> = HashMap::new();
+
+    let mut rest = html;
+
+    while let Some(start) = rest.find("<textarea") {
+        rest = &rest[start..];
+
+        let tag_end = rest.find('>').ok_or(anyhow!("unexpected replay form format"))?;
+        let tag = &rest[..tag_end];
+
+        let content_end = rest.find("</textarea>").ok_or(anyhow!("unexpected replay form format"))?;
+        let content = unescape_html(rest[tag_end + 1..content_end].trim_start_matches('\n'));
+
+        if let Some(name_start) = tag.find(FIELD_NAME_PREFIX) {
+            let name = &tag[name_start + FIELD_NAME_PREFIX.len()..];
+
+            if let Some(name_end) = name.find('"') {
+                let name = &name[..name_end];
+
+                if name == MAIN_SCRIPT_FIELD {
+                    main_script = Some(content);
+
+                } else {
+                    loaded_scripts.insert(name.to_string(), content);
+                }
+            }
+        }
+
+        rest = &rest[content_end..];
+    }
+
+    match main_script {
+        Some(main_script) => Ok(JenkinsReplayScripts { main_script, loaded_scripts }),
+        None => Err(anyhow!("main script not found in replay form"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jenkins::replay::parse_replay_form;
+
+    #[test]
+    fn replay_form_should_be_parsed() {
+        let html = r#"<form method="post" action="run" name="config">
+            <textarea name="_.mainScript" class="jenkins-input">
+pipeline { agent any; stages { stage('Build') { steps { sh 'make &amp;&amp; echo &quot;ok&quot;' } } } }</textarea>
+            <textarea name="_.Script1" class="jenkins-input">echo 'loaded'</textarea>
+            <textarea name="_.com_company_Utils" class="jenkins-input">def call() {}</textarea>
+        </form>"#;
+
+        let mut scripts = parse_replay_form(html).unwrap();
+
+        assert_eq!(r#"pipeline { agent any; stages { stage('Build') { steps { sh 'make && echo "ok"' } } } }"#,
+                   scripts.main_script);
+        assert_eq!(2, scripts.loaded_scripts.len());
+
+        scripts.set_loaded_script("com.company.Utils", "def call() { echo 'x' }".to_string()).unwrap();
+        assert_eq!("def call() { echo 'x' }", scripts.loaded_scripts.get("com_company_Utils").unwrap());
+
+        assert!(scripts.set_loaded_script("Unknown", "".to_string()).is_err());
+    }
+
+    #[test]
+    fn error_should_be_returned_without_main_script() {
+        assert!(parse_replay_form("<html></html>").is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use log::info;
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+
+use crate::jenkins::get_job_url;
+use crate::jenkins::list::JenkinsJob;
+
+const MAX_SUGGESTIONS: usize = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobNameResolution {
+    Found(String),
+    /// Candidates matching by prefix or substring
+    Ambiguous(Vec<String>),
+    /// Close matches by edit distance
+    Unknown(Vec<String>),
+}
+
+/// Exact match first, then case-insensitive match, unique prefix and unique substring (case-insensitive).
+pub fn resolve_job_name(name: &str, job_list: &[JenkinsJob]) -> JobNameResolution {
+    if job_list.iter().any(|j| j.name == name) {
+        return JobNameResolution::Found(name.to_string())
+    }
+
+    let lowercase_name = name.to_lowercase();
+
+    let matchers: [&dyn Fn(&str) -> bool; 3] = [
+        &|j| j == lowercase_name,
+        &|j| j.starts_with(&lowercase_name),
+        &|j| j.contains(&lowercase_name),
+    ];
+
+    for matcher in matchers {
+        let candidates = job_list.iter()
+            .filter(|j| matcher(&j.name.to_lowercase()))
+            .map(|j| j.name.to_string())
+            .collect::<Vec<String>>();
+
+        match candidates.as_slice() {
+            [] => continue,
+            [job_name] => return JobNameResolution::Found(job_name.to_string()),
+            _ => return JobNameResolution::Ambiguous(candidates)
+        }
+    }
+
+    JobNameResolution::Unknown(get_close_matches(&lowercase_name, job_list))
+}
+
+/// Job list contains top level jobs only and might be cached, so job in folder
+/// or created recently is checked on server.
+pub fn is_job_exists(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                     job_name: &str) -> anyhow::Result<bool> {
+    info!("check if job '{job_name}' exists..");
+
+    let url = format!("{}/api/json?tree=name", get_job_url(jenkins_url, job_name));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?;
+
+    match resp.status() {
+        StatusCode::NOT_FOUND => Ok(false),
+        _ => resp.error_for_status().map(|_| true).map_err(|e| e.into())
+    }
+}
+
+fn get_close_matches(name: &str, job_list: &[JenkinsJob]) -> Vec<String> {
+    let max_distance = (name.chars().count() / 3).max(2);
+
+    let mut matches = job_list.iter()
+        .map(|j| (get_edit_distance(name, &j.name.to_lowercase()), j.name.to_string()))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect::<Vec<(usize, String)>>();
+
+    matches.sort();
+
+    matches.into_iter().take(MAX_SUGGESTIONS).map(|(_, name)| name).collect()
+}
+
+/// Levenshtein distance
+fn get_edit_distance(left: &str, right: &str) -> usize {
+    let right = right.chars().collect::<Vec<char>>();
+
+    let mut previous = (0..=right.len()).collect::<Vec<usize>>();
+
+    for (i, left_char) in left.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, right_char) in right.iter().enumerate() {
+            let substitution = previous[j] + usize::from(left_char != *right_char);
+
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[right.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jenkins::list::JenkinsJob;
+    use crate::jenkins::resolve::{get_edit_distance, JobNameResolution, resolve_job_name};
+
+    #[test]
+    fn job_name_should_be_resolved() {
+        let job_list = ["api-build", "api-deploy-dev", "api-deploy-prod", "web-deploy-dev"].iter()
+            .map(|name| JenkinsJob { name: name.to_string(), url: format!("https://jenkins.company.com/job/{name}") })
+            .collect::<Vec<JenkinsJob>>();
+
+        assert_eq!(JobNameResolution::Found("api-build".to_string()), resolve_job_name("api-build", &job_list));
+        assert_eq!(JobNameResolution::Found("api-build".to_string()), resolve_job_name("API-B", &job_list));
+        assert_eq!(JobNameResolution::Found("web-deploy-dev".to_string()), resolve_job_name("web", &job_list));
+        assert_eq!(JobNameResolution::Found("api-deploy-prod".to_string()), resolve_job_name("prod", &job_list));
+
+        assert_eq!(JobNameResolution::Ambiguous(vec!["api-deploy-dev".to_string(), "api-deploy-prod".to_string()]),
+                   resolve_job_name("api-deploy", &job_list));
+
+        assert_eq!(JobNameResolution::Unknown(vec!["api-build".to_string()]),
+                   resolve_job_name("apy-biuld", &job_list));
+    }
+
+    #[test]
+    fn edit_distance_should_be_calculated() {
+        assert_eq!(0, get_edit_distance("deploy", "deploy"));
+        assert_eq!(2, get_edit_distance("deploy", "deplyo"));
+        assert_eq!(3, get_edit_distance("kitten", "sitting"));
+        assert_eq!(4, get_edit_distance("", "test"));
+    }
+}
@@ -0,0 +1,112 @@
+use anyhow::{anyhow, Context};
+use log::info;
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use serde_json::json;
+
+use crate::jenkins::auth::get_crumb_issuer;
+use crate::jenkins::{get_build_url, unescape_html};
+
+const STAGE_NAME_FIELD: &str = "stageName";
+
+/// Declarative pipeline restart action doesn't have REST API, stages are taken from restart form.
+///
+/// Empty list is returned if build isn't eligible for restart (still running, not declarative, etc.)
+pub fn get_restartable_stages(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                              job_name: &str, build: &str) -> anyhow::Result<Vec<String>> {
+    info!("get restartable stages of build '{build}' for job '{job_name}'..");
+
+    let url = format!("{}/restart/", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?;
+
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Err(anyhow!("build '{build}' for job '{job_name}' can't be restarted, is it declarative pipeline?"))
+    }
+
+    let resp = resp.error_for_status()
+        .context(format!("unable to get restart form of build '{build}' for job '{job_name}'"))?;
+
+    let stages = parse_restart_form(&resp.text()?);
+
+    info!("restartable stages: {:?}", stages);
+
+    Ok(stages)
+}
+
+pub fn restart_from_stage(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                          job_name: &str, build: &str, stage: &str) -> anyhow::Result<()> {
+    info!("restart build '{build}' for job '{job_name}' from stage '{stage}'..");
+
+    let crumb_issuer = get_crumb_issuer(client, jenkins_url, username, token)?;
+
+    let form = vec![
+        (STAGE_NAME_FIELD.to_string(), stage.to_string()),
+        ("json".to_string(), json!({STAGE_NAME_FIELD: stage}).to_string()),
+        (crumb_issuer.crumb_request_field, crumb_issuer.crumb),
+    ];
+
+    let url = format!("{}/restart/restart", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.post(&url).basic_auth(username, Some(token)).form(&form).send()?;
+
+    info!("server response: {}", resp.status());
+
+    resp.error_for_status()
+        .context(format!("unable to restart build '{build}' for job '{job_name}' from stage '{stage}'"))?;
+
+    Ok(())
+}
+
+/// Options of `<select name="stageName">`.
+fn parse_restart_form(html: &str) -> Vec<String> {
+    let select_start = match html.find(&format!("name=\"{STAGE_NAME_FIELD}\"")) {
+        Some(start) => start,
+        None => return vec![]
+    };
+
+    let select = &html[select_start..];
+    let select = &select[..select.find("</select>").unwrap_or(select.len())];
+
+    let mut stages: Vec<String> = vec![];
+
+    let mut rest = select;
+
+    while let Some(start) = rest.find("<option") {
+        rest = &rest[start..];
+
+        let (tag_end, content_end) = match (rest.find('>'), rest.find("</option>")) {
+            (Some(tag_end), Some(content_end)) if tag_end < content_end => (tag_end, content_end),
+            _ => break
+        };
+
+        stages.push(unescape_html(rest[tag_end + 1..content_end].trim()));
+
+        rest = &rest[content_end..];
+    }
+
+    stages
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jenkins::restart::parse_restart_form;
+
+    #[test]
+    fn restartable_stages_should_be_parsed() {
+        let html = r#"<form method="post" action="restart">
+            <select name="stageName" class="setting-input">
+                <option value="Build">Build</option>
+                <option value="Test &amp; Lint">Test &amp; Lint</option>
+                <option value="Deploy">Deploy</option>
+            </select>
+        </form>"#;
+
+        assert_eq!(vec!["Build", "Test & Lint", "Deploy"], parse_restart_form(html));
+    }
+
+    #[test]
+    fn no_stages_should_be_returned_for_not_eligible_build() {
+        assert!(parse_restart_form("<div>This Pipeline cannot be restarted</div>").is_empty());
+    }
+}
@@ -0,0 +1,126 @@
+use std::ops::RangeInclusive;
+
+use anyhow::{anyhow, Context};
+use log::info;
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::jenkins::auth::get_crumb_issuer;
+use crate::jenkins::get_build_url;
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsBuildState {
+    pub number: u32,
+    #[serde(default)]
+    pub keep_log: bool,
+    #[serde(default)]
+    pub building: bool,
+}
+
+impl JenkinsBuildState {
+    /// Running builds can't be deleted, kept forever ones only with `force`.
+    pub fn is_protected_with(&self, force: bool) -> bool {
+        self.building || (self.keep_log && !force)
+    }
+}
+
+/// `None` if build doesn't exist.
+pub fn get_build_state(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                       job_name: &str, build: &str) -> anyhow::Result<Option<JenkinsBuildState>> {
+    info!("get state of build '{build}' for job '{job_name}'..");
+
+    let url = format!("{}/api/json?tree=number,keepLog,building", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?;
+
+    if resp.status() == StatusCode::NOT_FOUND {
+        return Ok(None)
+    }
+
+    let resp = resp.error_for_status()
+        .context(format!("unable to get state of build '{build}' for job '{job_name}'"))?;
+
+    Ok(Some(resp.json::<JenkinsBuildState>()?))
+}
+
+/// Jenkins only toggles flag, so current state should be checked first (see `get_build_state`).
+pub fn toggle_keep_build(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                         job_name: &str, build: &str) -> anyhow::Result<()> {
+    info!("toggle keep forever of build '{build}' for job '{job_name}'..");
+
+    post_build_action(client, jenkins_url, username, token, job_name, build, "toggleLogKeep")
+}
+
+pub fn delete_build(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                    job_name: &str, build: &str) -> anyhow::Result<()> {
+    info!("delete build '{build}' for job '{job_name}'..");
+
+    post_build_action(client, jenkins_url, username, token, job_name, build, "doDelete")
+}
+
+fn post_build_action(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                     job_name: &str, build: &str, action: &str) -> anyhow::Result<()> {
+    let crumb_issuer = get_crumb_issuer(client, jenkins_url, username, token)?;
+
+    let url = format!("{}/{action}", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.post(&url).basic_auth(username, Some(token))
+        .header(&crumb_issuer.crumb_request_field, &crumb_issuer.crumb)
+        .send()?;
+
+    info!("server response: {}", resp.status());
+
+    resp.error_for_status()
+        .context(format!("unable to {action} build '{build}' for job '{job_name}'"))?;
+
+    Ok(())
+}
+
+/// `42` or `40-45`
+pub fn parse_build_range(value: &str) -> anyhow::Result<RangeInclusive<u32>> {
+    let parse = |v: &str| v.trim().parse::<u32>()
+        .map_err(|_| anyhow!("invalid build number '{v}', expected number or range like 40-45"));
+
+    let range = match value.split_once('-') {
+        Some((from, to)) => parse(from)?..=parse(to)?,
+        None => {
+            let number = parse(value)?;
+            number..=number
+        }
+    };
+
+    if range.is_empty() {
+        return Err(anyhow!("invalid build range '{value}'"))
+    }
+
+    Ok(range)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jenkins::retention::{JenkinsBuildState, parse_build_range};
+
+    #[test]
+    fn build_range_should_be_parsed() {
+        assert_eq!(42..=42, parse_build_range("42").unwrap());
+        assert_eq!(40..=45, parse_build_range("40-45").unwrap());
+
+        assert!(parse_build_range("45-40").is_err());
+        assert!(parse_build_range("lastBuild").is_err());
+    }
+
+    #[test]
+    fn kept_and_running_builds_should_be_protected() {
+        let state = serde_json::from_str::<JenkinsBuildState>(r#"{"number": 42, "keepLog": true, "building": false}"#).unwrap();
+        assert!(state.is_protected_with(false));
+        assert!(!state.is_protected_with(true));
+
+        let state = serde_json::from_str::<JenkinsBuildState>(r#"{"number": 43, "keepLog": false, "building": true}"#).unwrap();
+        assert!(state.is_protected_with(true));
+
+        let state = serde_json::from_str::<JenkinsBuildState>(r#"{"number": 44, "keepLog": false, "building": false}"#).unwrap();
+        assert!(!state.is_protected_with(false));
+    }
+}
@@ -0,0 +1,233 @@
+use std::fmt::{Display, Formatter};
+
+use anyhow::{anyhow, Context};
+use log::info;
+use reqwest::blocking::Client;
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::jenkins::{format_duration, get_build_url, unescape_html};
+
+/// Pipeline REST API: `<build>/wfapi/describe`
+///
+/// ```json
+/// {
+///     "id": "42",
+///     "status": "IN_PROGRESS",
+///     "durationMillis": 312000,
+///     "stages": [
+///         {
+///             "id": "6",
+///             "name": "Build",
+///             "status": "SUCCESS",
+///             "durationMillis": 120000,
+///             "pauseDurationMillis": 0,
+///             "_links": {"self": {"href": "/job/api/42/execution/node/6/wfapi/describe"}}
+///         }
+///     ]
+/// }
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsPipelineRun {
+    pub id: String,
+    pub status: String,
+    pub duration_millis: u64,
+    pub stages: Vec<JenkinsPipelineStage>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsPipelineStage {
+    pub id: String,
+    pub name: String,
+    pub status: String,
+    pub duration_millis: u64,
+    #[serde(default)]
+    pub pause_duration_millis: u64,
+    #[serde(rename = "_links")]
+    pub links: JenkinsLinks,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsLinks {
+    #[serde(rename = "self")]
+    pub self_link: Option<JenkinsLink>,
+    pub log: Option<JenkinsLink>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsLink {
+    pub href: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsStageDetails {
+    pub stage_flow_nodes: Vec<JenkinsStageFlowNode>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsStageFlowNode {
+    pub name: String,
+    #[serde(rename = "_links")]
+    pub links: JenkinsLinks,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsNodeLog {
+    pub text: Option<String>,
+    #[serde(default)]
+    pub has_more: bool,
+    pub console_url: Option<String>,
+}
+
+pub fn get_pipeline_run(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                        job_name: &str, build: &str) -> anyhow::Result<JenkinsPipelineRun> {
+    info!("get pipeline stages of build '{build}' for job '{job_name}'..");
+
+    let url = format!("{}/wfapi/describe", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get stages of build '{build}' for job '{job_name}', is it pipeline job?"))?;
+
+    let run = resp.json::<JenkinsPipelineRun>()?;
+
+    info!("pipeline run: {:?}", run);
+
+    Ok(run)
+}
+
+/// Logs of all stage nodes, node names are used as headers.
+pub fn get_stage_log(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                     stage: &JenkinsPipelineStage) -> anyhow::Result<String> {
+    info!("get log of stage '{}'..", stage.name);
+
+    let self_link = stage.links.self_link.as_ref()
+        .ok_or(anyhow!("stage '{}' doesn't have details link", stage.name))?;
+
+    let details = get_json::<JenkinsStageDetails>(client, jenkins_url, username, token, &self_link.href)?;
+
+    let mut lines: Vec<String> = vec![];
+
+    for node in details.stage_flow_nodes {
+        if let Some(log_link) = &node.links.log {
+            let log = get_json::<JenkinsNodeLog>(client, jenkins_url, username, token, &log_link.href)?;
+
+            lines.push(format!("--- {} ---", node.name));
+            lines.push(strip_html(log.text.as_deref().unwrap_or("")));
+
+            if log.has_more {
+                lines.push(format!("(log is truncated, see {})", log.console_url.unwrap_or_default()));
+            }
+        }
+    }
+
+    Ok(lines.join("\n"))
+}
+
+/// Links from Pipeline REST API are absolute paths on server.
+fn get_json<T: serde::de::DeserializeOwned>(client: &Client, jenkins_url: &str, username: &str,
+                                            token: &str, href: &str) -> anyhow::Result<T> {
+    let url = Url::parse(jenkins_url)?.join(href)?;
+
+    let resp = client.get(url.as_str()).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get '{url}'"))?;
+
+    Ok(resp.json::<T>()?)
+}
+
+/// Node logs are html with console annotations.
+fn strip_html(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut inside_tag = false;
+
+    for c in text.chars() {
+        match c {
+            '<' => inside_tag = true,
+            '>' if inside_tag => inside_tag = false,
+            _ if !inside_tag => result.push(c),
+            _ => {}
+        }
+    }
+
+    unescape_html(&result)
+}
+
+impl JenkinsPipelineRun {
+    pub fn find_stage(&self, name: &str) -> Option<&JenkinsPipelineStage> {
+        self.stages.iter().find(|s| s.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl Display for JenkinsPipelineRun {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "build #{}: {} ({})", self.id, self.status, format_duration(self.duration_millis))?;
+
+        let name_width = self.stages.iter().map(|s| s.name.len()).max().unwrap_or(0);
+        let status_width = self.stages.iter().map(|s| s.status.len()).max().unwrap_or(0);
+
+        for stage in &self.stages {
+            write!(f, "\n  {:name_width$}  {:status_width$}  {}", stage.name, stage.status,
+                   format_duration(stage.duration_millis))?;
+
+            if stage.pause_duration_millis > 0 {
+                write!(f, " (paused {})", format_duration(stage.pause_duration_millis))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jenkins::stages::{JenkinsPipelineRun, strip_html};
+
+    #[test]
+    fn pipeline_run_should_be_deserialized() {
+        let input = r##"{
+            "_links": {"self": {"href": "/job/api/42/wfapi/describe"}},
+            "id": "42",
+            "name": "#42",
+            "status": "PAUSED_PENDING_INPUT",
+            "durationMillis": 312000,
+            "stages": [
+                {
+                    "_links": {"self": {"href": "/job/api/42/execution/node/6/wfapi/describe"}},
+                    "id": "6",
+                    "name": "Build",
+                    "status": "SUCCESS",
+                    "durationMillis": 120000,
+                    "pauseDurationMillis": 0
+                },
+                {
+                    "_links": {"self": {"href": "/job/api/42/execution/node/20/wfapi/describe"}},
+                    "id": "20",
+                    "name": "Deploy",
+                    "status": "PAUSED_PENDING_INPUT",
+                    "durationMillis": 60000,
+                    "pauseDurationMillis": 55000
+                }
+            ]
+        }"##;
+
+        let run = serde_json::from_str::<JenkinsPipelineRun>(input).unwrap();
+
+        assert_eq!(2, run.stages.len());
+        assert_eq!("20", run.find_stage("deploy").unwrap().id);
+        assert!(run.to_string().contains("Deploy  PAUSED_PENDING_INPUT  1m 0s (paused 55s)"));
+    }
+
+    #[test]
+    fn html_should_be_stripped() {
+        assert_eq!("+ make build\nok & done",
+                   strip_html("<span class=\"pipeline-node-7\">+ make build\n</span>ok &amp; done"));
+    }
+}
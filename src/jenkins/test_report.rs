@@ -0,0 +1,236 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+
+use anyhow::Context;
+use log::info;
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::jenkins::get_build_url;
+
+const STACK_TRACE_LINES: usize = 5;
+
+const FAILED_STATUSES: [&str; 2] = ["FAILED", "REGRESSION"];
+const SKIPPED_STATUS: &str = "SKIPPED";
+
+/// Test report from `<build>/testReport/api/json`, pipeline jobs might return
+/// aggregated report with `childReports` instead of `suites`.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsTestReport {
+    #[serde(default)]
+    pub suites: Vec<JenkinsTestSuite>,
+    #[serde(default)]
+    pub child_reports: Vec<JenkinsChildTestReport>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsChildTestReport {
+    pub result: JenkinsTestReport,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsTestSuite {
+    pub name: String,
+    #[serde(default)]
+    pub duration: f64,
+    pub cases: Vec<JenkinsTestCase>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsTestCase {
+    pub class_name: String,
+    pub name: String,
+    pub status: String,
+    #[serde(default)]
+    pub duration: f64,
+    pub error_details: Option<String>,
+    pub error_stack_trace: Option<String>,
+}
+
+impl JenkinsTestCase {
+    pub fn is_failed(&self) -> bool {
+        FAILED_STATUSES.contains(&self.status.as_str())
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        self.status == SKIPPED_STATUS
+    }
+}
+
+impl JenkinsTestReport {
+    pub fn get_suites(&self) -> Vec<&JenkinsTestSuite> {
+        let mut suites = self.suites.iter().collect::<Vec<&JenkinsTestSuite>>();
+
+        self.child_reports.iter().for_each(|r| suites.extend(r.result.get_suites()));
+
+        suites
+    }
+
+    fn get_cases(&self) -> Vec<&JenkinsTestCase> {
+        self.get_suites().into_iter().flat_map(|s| s.cases.iter()).collect()
+    }
+
+    pub fn get_failed_cases(&self) -> Vec<&JenkinsTestCase> {
+        self.get_cases().into_iter().filter(|c| c.is_failed()).collect()
+    }
+
+    /// Returns (passed, failed, skipped)
+    pub fn get_totals(&self) -> (usize, usize, usize) {
+        let cases = self.get_cases();
+
+        let failed = cases.iter().filter(|c| c.is_failed()).count();
+        let skipped = cases.iter().filter(|c| c.is_skipped()).count();
+
+        (cases.len() - failed - skipped, failed, skipped)
+    }
+}
+
+/// Returns `None` if build doesn't have test report.
+pub fn get_test_report(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                       job_name: &str, build: &str) -> anyhow::Result<Option<JenkinsTestReport>> {
+    info!("get test report of build '{build}' for job '{job_name}'..");
+
+    let url = format!("{}/testReport/api/json", get_build_url(jenkins_url, job_name, build));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?;
+
+    if resp.status() == StatusCode::NOT_FOUND {
+        info!("build '{build}' doesn't have test report");
+        return Ok(None)
+    }
+
+    let resp = resp.error_for_status()
+        .context(format!("unable to get test report of build '{build}' for job '{job_name}'"))?;
+
+    let report = resp.json::<JenkinsTestReport>()?;
+
+    Ok(Some(report))
+}
+
+pub fn export_test_report_to_junit(report: &JenkinsTestReport, file_path: &Path) -> anyhow::Result<()> {
+    info!("export test report to '{}'..", file_path.display());
+
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        "<testsuites>".to_string()
+    ];
+
+    for suite in report.get_suites() {
+        let failures = suite.cases.iter().filter(|c| c.is_failed()).count();
+        let skipped = suite.cases.iter().filter(|c| c.is_skipped()).count();
+
+        lines.push(format!(r#"  <testsuite name="{}" tests="{}" failures="{failures}" skipped="{skipped}" time="{}">"#,
+                           escape_xml(&suite.name), suite.cases.len(), suite.duration));
+
+        for case in &suite.cases {
+            lines.push(format!(r#"    <testcase classname="{}" name="{}" time="{}">"#,
+                               escape_xml(&case.class_name), escape_xml(&case.name), case.duration));
+
+            if case.is_failed() {
+                lines.push(format!(r#"      <failure message="{}">{}</failure>"#,
+                                   escape_xml(case.error_details.as_deref().unwrap_or("")),
+                                   escape_xml(case.error_stack_trace.as_deref().unwrap_or(""))));
+
+            } else if case.is_skipped() {
+                lines.push("      <skipped/>".to_string());
+            }
+
+            lines.push("    </testcase>".to_string());
+        }
+
+        lines.push("  </testsuite>".to_string());
+    }
+
+    lines.push("</testsuites>".to_string());
+
+    fs::write(file_path, lines.join("\n"))
+        .context(format!("unable to write file '{}'", file_path.display()))?;
+
+    Ok(())
+}
+
+fn escape_xml(value: &str) -> String {
+    value.replace('&', "&amp;")
+         .replace('<', "&lt;")
+         .replace('>', "&gt;")
+         .replace('"', "&quot;")
+         .replace('\'', "&apos;")
+}
+
+impl Display for JenkinsTestReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (passed, failed, skipped) = self.get_totals();
+
+        write!(f, "tests: {passed} passed, {failed} failed, {skipped} skipped")?;
+
+        for case in self.get_failed_cases() {
+            write!(f, "\n  {}.{}", case.class_name, case.name)?;
+
+            if let Some(details) = &case.error_details {
+                write!(f, "\n    error: {}", details.trim())?;
+            }
+
+            if let Some(stack_trace) = &case.error_stack_trace {
+                for line in stack_trace.lines().take(STACK_TRACE_LINES) {
+                    write!(f, "\n      {}", line.trim())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jenkins::test_report::{escape_xml, JenkinsTestReport};
+
+    #[test]
+    fn totals_should_be_calculated() {
+        let input = r#"{
+            "failCount": 1,
+            "suites": [{
+                "name": "com.company.ApiTest",
+                "duration": 1.5,
+                "cases": [
+                    {"className": "com.company.ApiTest", "name": "ok", "status": "PASSED", "duration": 0.5},
+                    {"className": "com.company.ApiTest", "name": "fixed", "status": "FIXED", "duration": 0.5},
+                    {"className": "com.company.ApiTest", "name": "ignored", "status": "SKIPPED", "duration": 0},
+                    {"className": "com.company.ApiTest", "name": "broken", "status": "REGRESSION", "duration": 0.5,
+                     "errorDetails": "expected 1", "errorStackTrace": "at ApiTest.broken"}
+                ]
+            }]
+        }"#;
+
+        let report = serde_json::from_str::<JenkinsTestReport>(input).unwrap();
+
+        assert_eq!((2, 1, 1), report.get_totals());
+        assert_eq!("broken", report.get_failed_cases().first().unwrap().name);
+    }
+
+    #[test]
+    fn aggregated_report_should_be_supported() {
+        let input = r#"{
+            "childReports": [{
+                "result": {
+                    "suites": [{"name": "s", "cases": [{"className": "A", "name": "a", "status": "FAILED"}]}]
+                }
+            }]
+        }"#;
+
+        let report = serde_json::from_str::<JenkinsTestReport>(input).unwrap();
+
+        assert_eq!((0, 1, 0), report.get_totals());
+    }
+
+    #[test]
+    fn xml_should_be_escaped() {
+        assert_eq!("a &lt; b &amp;&amp; &quot;c&quot;", escape_xml(r#"a < b && "c""#));
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::Context;
+use log::info;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+use crate::jenkins::list::{JenkinsApiResponse, JenkinsJob};
+
+/// Nested views (Nested View plugin) are fetched up to this depth.
+const NESTED_VIEWS_DEPTH: usize = 4;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsView {
+    pub name: String,
+    #[serde(default)]
+    pub views: Vec<JenkinsView>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsViewsResponse {
+    #[serde(default)]
+    pub views: Vec<JenkinsView>,
+}
+
+/// Url for view by full name, nested views are supported:
+///
+/// `team/backend` -> `https://jenkins.company.com/view/team/view/backend`
+pub fn get_view_url(jenkins_url: &str, view_name: &str) -> String {
+    let path = view_name.split('/')
+        .filter(|part| !part.is_empty())
+        .map(|part| format!("view/{}", urlencoding::encode(part)))
+        .collect::<Vec<String>>()
+        .join("/");
+
+    format!("{}/{path}", jenkins_url.trim_end_matches('/'))
+}
+
+/// Full names of all views, nested views are `parent/child`.
+pub fn get_jenkins_views(client: &Client, jenkins_url: &str,
+                         username: &str, token: &str) -> anyhow::Result<Vec<String>> {
+    info!("get views from jenkins '{jenkins_url}'..");
+
+    let url = format!("{}/api/json?tree={}", jenkins_url.trim_end_matches('/'), get_views_tree(NESTED_VIEWS_DEPTH));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context("unable to get views")?;
+
+    let resp = resp.json::<JenkinsViewsResponse>()?;
+
+    info!("views: {:?}", resp.views);
+
+    let mut names: Vec<String> = vec![];
+    collect_view_names(&resp.views, "", &mut names);
+
+    Ok(names)
+}
+
+pub fn get_view_job_list(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                         view_name: &str) -> anyhow::Result<Vec<JenkinsJob>> {
+    info!("get job list of view '{view_name}'..");
+
+    let url = format!("{}/api/json?tree=jobs[name,url]", get_view_url(jenkins_url, view_name));
+
+    let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+        .error_for_status()
+        .context(format!("unable to get jobs of view '{view_name}'"))?;
+
+    let resp = resp.json::<JenkinsApiResponse>()?;
+
+    info!("jobs: {:?}", resp.jobs);
+
+    Ok(resp.jobs)
+}
+
+/// `views[name,views[name]]` for depth 2
+fn get_views_tree(depth: usize) -> String {
+    match depth {
+        0 | 1 => "views[name]".to_string(),
+        _ => format!("views[name,{}]", get_views_tree(depth - 1))
+    }
+}
+
+fn collect_view_names(views: &[JenkinsView], prefix: &str, names: &mut Vec<String>) {
+    for view in views {
+        let name = format!("{prefix}{}", view.name);
+
+        names.push(name.to_string());
+
+        collect_view_names(&view.views, &format!("{name}/"), names);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jenkins::views::{collect_view_names, get_view_url, get_views_tree, JenkinsViewsResponse};
+
+    #[test]
+    fn nested_views_should_be_collected() {
+        let input = r#"{"views": [
+            {"name": "all"},
+            {"name": "team", "views": [{"name": "backend"}, {"name": "web", "views": [{"name": "legacy"}]}]}
+        ]}"#;
+
+        let resp = serde_json::from_str::<JenkinsViewsResponse>(input).unwrap();
+
+        let mut names: Vec<String> = vec![];
+        collect_view_names(&resp.views, "", &mut names);
+
+        assert_eq!(vec!["all", "team", "team/backend", "team/web", "team/web/legacy"], names);
+    }
+
+    #[test]
+    fn view_url_and_tree_should_be_built() {
+        assert_eq!("https://jenkins.company.com/view/team/view/back%20end",
+                   get_view_url("https://jenkins.company.com/", "team/back end"));
+
+        assert_eq!("views[name,views[name]]", get_views_tree(2));
+    }
+}
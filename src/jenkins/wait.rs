@@ -0,0 +1,168 @@
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use log::info;
+use reqwest::blocking::Client;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Queue item which has left the queue, it never gets `executable` later.
+const LEFT_ITEM_CLASS: &str = "hudson.model.Queue$LeftItem";
+
+/// https://www.jenkins.io/doc/book/using/remote-access-api/
+///
+/// ```json
+/// {
+///     "_class": "hudson.model.Queue$LeftItem",
+///     "cancelled": false,
+///     "executable": {
+///         "number": 42,
+///         "url": "https://jenkins.company.com/job/api/42/"
+///     }
+/// }
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct JenkinsQueueItem {
+    #[serde(rename = "_class", default)]
+    pub class: String,
+    pub cancelled: Option<bool>,
+    pub why: Option<String>,
+    pub executable: Option<JenkinsBuildRef>,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsBuildRef {
+    pub number: u32,
+    pub url: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JenkinsBuildResult {
+    pub number: u32,
+    pub url: String,
+    pub building: bool,
+    pub result: Option<String>,
+    pub duration: u64,
+}
+
+impl JenkinsBuildResult {
+    pub fn is_success(&self) -> bool {
+        self.result.as_deref() == Some("SUCCESS")
+    }
+}
+
+/// Wait until queue item (see `build_job`) becomes build. Cancelled, left without build
+/// or removed (jenkins keeps left items for a few minutes only) queue item is an error.
+pub fn wait_for_build_start(client: &Client, username: &str, token: &str,
+                            queue_item_url: &str) -> anyhow::Result<JenkinsBuildRef> {
+    info!("wait for queue item '{queue_item_url}'..");
+
+    let url = format!("{}/api/json", queue_item_url.trim_end_matches('/'));
+
+    loop {
+        let resp = client.get(&url).basic_auth(username, Some(token)).send()?;
+
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Err(anyhow!("queue item '{queue_item_url}' doesn't exist anymore"))
+        }
+
+        let resp = resp.error_for_status()
+            .context(format!("unable to get queue item '{queue_item_url}'"))?;
+
+        let queue_item = resp.json::<JenkinsQueueItem>()?;
+
+        info!("queue item: {:?}", queue_item);
+
+        if queue_item.cancelled.unwrap_or(false) {
+            return Err(anyhow!("queue item '{queue_item_url}' has been cancelled"))
+        }
+
+        if let Some(build) = queue_item.executable {
+            return Ok(build)
+        }
+
+        if queue_item.class == LEFT_ITEM_CLASS {
+            return Err(anyhow!("queue item '{queue_item_url}' has left the queue without build"))
+        }
+
+        if let Some(why) = &queue_item.why {
+            info!("build is waiting: {why}");
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// Wait until build is finished.
+pub fn wait_for_build_result(client: &Client, username: &str, token: &str,
+                             build_url: &str) -> anyhow::Result<JenkinsBuildResult> {
+    info!("wait for build '{build_url}'..");
+
+    let url = format!("{}/api/json?tree=number,url,building,result,duration",
+                      build_url.trim_end_matches('/'));
+
+    loop {
+        let resp = client.get(&url).basic_auth(username, Some(token)).send()?
+            .error_for_status()
+            .context(format!("unable to get build '{build_url}'"))?;
+
+        let build = resp.json::<JenkinsBuildResult>()?;
+
+        info!("build: {:?}", build);
+
+        if !build.building {
+            return Ok(build)
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jenkins::wait::{JenkinsBuildRef, JenkinsQueueItem, LEFT_ITEM_CLASS};
+
+    #[test]
+    fn waiting_queue_item_should_be_deserialized() {
+        let input = r#"{
+            "blocked": false,
+            "cancelled": false,
+            "why": "Waiting for next available executor",
+            "executable": null
+        }"#;
+
+        let item = serde_json::from_str::<JenkinsQueueItem>(input).unwrap();
+
+        assert!(item.executable.is_none());
+        assert_ne!(LEFT_ITEM_CLASS, item.class);
+    }
+
+    #[test]
+    fn left_queue_item_should_be_deserialized() {
+        let input = r#"{"_class": "hudson.model.Queue$LeftItem", "cancelled": false, "executable": null}"#;
+
+        let item = serde_json::from_str::<JenkinsQueueItem>(input).unwrap();
+
+        assert_eq!(LEFT_ITEM_CLASS, item.class);
+        assert!(item.executable.is_none());
+    }
+
+    #[test]
+    fn started_queue_item_should_be_deserialized() {
+        let input = r#"{
+            "cancelled": false,
+            "executable": {"_class": "hudson.model.FreeStyleBuild", "number": 42, "url": "https://jenkins.company.com/job/api/42/"}
+        }"#;
+
+        let item = serde_json::from_str::<JenkinsQueueItem>(input).unwrap();
+
+        assert_eq!(Some(JenkinsBuildRef { number: 42, url: "https://jenkins.company.com/job/api/42/".to_string() }),
+                   item.executable);
+    }
+}
@@ -4,19 +4,21 @@ use log4rs::encode::pattern::PatternEncoder;
 use log4rs::filter::threshold::ThresholdFilter;
 use log::LevelFilter;
 
+use crate::dirs::get_state_dir;
+
 const FILE_APPENDER_NAME: &str = "file";
 
-const LOG_FILE_PATH: &str = "jencli.log";
+const LOG_FILE: &str = "jencli.log";
 
 fn get_logging_level_from_string(level: &str) -> LevelFilter {
-    return match level {
+    match level {
         "debug" => LevelFilter::Debug,
         "error" => LevelFilter::Error,
         "warn" => LevelFilter::Warn,
         "trace" => LevelFilter::Trace,
         "off" => LevelFilter::Off,
         _ => LevelFilter::Info
-    };
+    }
 }
 
 pub fn get_logging_config(logging_level: &str) -> Config {
@@ -31,7 +33,7 @@ pub fn get_logging_config(logging_level: &str) -> Config {
             Root::builder()
                 .appender(FILE_APPENDER_NAME)
                 .build(level)
-        ).expect(&format!("unable to create log file '{}'", LOG_FILE_PATH))
+        ).unwrap_or_else(|_| panic!("unable to create log file '{}'", get_state_dir().join(LOG_FILE).display()))
 }
 
 fn get_file_appender_definition(level: LevelFilter) -> Appender {
@@ -44,7 +46,7 @@ fn get_file_appender_definition(level: LevelFilter) -> Appender {
 fn get_file_appender() -> FileAppender {
     FileAppender::builder()
         .encoder(get_encoder())
-        .build(LOG_FILE_PATH)
+        .build(get_state_dir().join(LOG_FILE))
         .unwrap()
 }
 
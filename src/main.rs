@@ -1,30 +1,149 @@
-use std::env;
-use std::path::Path;
+use std::collections::HashMap;
+use std::{env, fs, io};
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::slice;
+use std::time::Duration;
 
-use anyhow::Context;
+use anyhow::{anyhow, Context};
+use chrono::Local;
 use clap::{Arg, ArgAction, ArgMatches, Command};
-use log::info;
-use reqwest::blocking::ClientBuilder;
+use clap::parser::ValueSource;
+use log::{info, warn};
+use reqwest::blocking::{Client, ClientBuilder};
 
-use crate::config::file::load_config_from_file;
-use crate::jenkins::build::build_job;
-use crate::jenkins::list::{get_jenkins_job_list, JenkinsJob};
+use crate::cache::{CacheMode, clear_cache, get_cache_status};
+use crate::config::AppConfig;
+use crate::config::file::{get_config_file_path, load_config_from_file, save_favorites_to_file};
+use crate::jenkins::duplicates::{confirm, DuplicateBuildPolicy};
+use crate::jenkins::bulk::{BuildOutcome, BuildSummary, confirm_build, RetryBackoff, RetryPolicy, trigger_build,
+                          trigger_build_with_retry, trigger_builds};
+use crate::jenkins::artifacts::{download_artifact, download_artifacts_archive, get_build_artifacts, matches_glob};
+use crate::jenkins::build::{build_job, BuildTrigger};
+use crate::jenkins::describe::{set_build_description, set_build_display_name};
+use crate::jenkins::diff::get_builds_diff;
+use crate::jenkins::{format_duration, format_size, is_url, JenkinsUrl, parse_duration, parse_jenkins_url, select_build};
+use crate::jenkins::input::{abort_input, get_pending_input_actions, JenkinsInputAction, proceed_input};
+use crate::history::{load_job_usage, record_job_usage};
+use crate::jenkins::list::{filter_job_list, get_favorite_job_list, get_jenkins_job_list, get_jenkins_job_status,
+                           get_jenkins_job_statuses, is_job_excluded, JenkinsJob, JenkinsJobStatus, JobSort, JobStatus,
+                           JobStatusFilter, sort_job_list, sort_job_statuses};
+use crate::jenkins::resolve::{is_job_exists, JobNameResolution, resolve_job_name};
+use crate::jenkins::replay::{get_replay_scripts, replay_build};
+use crate::jenkins::retention::{delete_build, get_build_state, JenkinsBuildState, parse_build_range, toggle_keep_build};
+use crate::jenkins::restart::{get_restartable_stages, restart_from_stage};
+use crate::jenkins::views::{get_jenkins_views, get_view_job_list};
+use crate::jenkins::stages::{get_pipeline_run, get_stage_log};
+use crate::jenkins::test_report::{export_test_report_to_junit, get_test_report};
 use crate::logging::get_logging_config;
+use crate::plan::{load_plan_from_file, run_plan};
+use crate::scheduler::{add_scheduled_builds, load_scheduled_builds, parse_schedule_time, save_scheduled_builds, schedule_build,
+                       split_due_builds};
 
 pub mod logging;
 pub mod config;
 pub mod jenkins;
 pub mod cache;
+pub mod plan;
+pub mod scheduler;
+pub mod history;
+pub mod dirs;
 
 const WORK_DIR_ARG: &str = "work-dir";
 const WORK_DIR_SHORT_ARG: char = 'd';
 
+const REFRESH_ARG: &str = "refresh";
+const NO_CACHE_ARG: &str = "no-cache";
+
 const LIST_COMMAND: &str = "list";
 const MASK_ARG: &str = "mask";
+const STATUS_ARG: &str = "status";
+const BUILT_SINCE_ARG: &str = "built-since";
+const NOT_BUILT_SINCE_ARG: &str = "not-built-since";
+const VIEW_ARG: &str = "view";
+const ALL_ARG: &str = "all";
+const SORT_ARG: &str = "sort";
+const FAVORITES_ARG: &str = "favorites";
+const REVERSE_ARG: &str = "reverse";
+
+const VIEWS_COMMAND: &str = "views";
 
 const BUILD_COMMAND: &str = "build";
 const NAME_ARG: &str = "name";
+const WAIT_ARG: &str = "wait";
+const STDIN_ARG: &str = "stdin";
+const PARALLEL_ARG: &str = "parallel";
+const RETRY_ARG: &str = "retry";
+const RETRY_ON_ARG: &str = "retry-on";
+const RETRY_DELAY_ARG: &str = "retry-delay";
+const RETRY_BACKOFF_ARG: &str = "retry-backoff";
+const IF_IDLE_ARG: &str = "if-idle";
+const CANCEL_RUNNING_ARG: &str = "cancel-running";
+const ALLOW_DUPLICATES_ARG: &str = "allow-duplicates";
+const DESCRIPTION_ARG: &str = "description";
+const DELAY_ARG: &str = "delay";
+const AT_ARG: &str = "at";
+
+const DIFF_COMMAND: &str = "diff";
+const JOB_ARG: &str = "job";
+const FROM_BUILD_ARG: &str = "from";
+const TO_BUILD_ARG: &str = "to";
+
+const ARTIFACTS_COMMAND: &str = "artifacts";
+const BUILD_ARG: &str = "build";
+const DOWNLOAD_ARG: &str = "download";
+const DEST_ARG: &str = "dest";
+
+const TESTS_COMMAND: &str = "tests";
+const JUNIT_ARG: &str = "junit";
+
+const STAGES_COMMAND: &str = "stages";
+const LOG_ARG: &str = "log";
+
+const INPUT_COMMAND: &str = "input";
+const PROCEED_ARG: &str = "proceed";
+const ABORT_ARG: &str = "abort";
+const PARAM_ARG: &str = "param";
+
+const REPLAY_COMMAND: &str = "replay";
+const SCRIPT_ARG: &str = "script";
+const LOADED_SCRIPT_ARG: &str = "loaded-script";
+const FETCH_ARG: &str = "fetch";
+
+const MAIN_SCRIPT_FILE: &str = "Jenkinsfile";
+
+const RUN_COMMAND: &str = "run";
+const PLAN_ARG: &str = "plan";
+
+const RESTART_COMMAND: &str = "restart";
+const STAGE_ARG: &str = "stage";
+
+const DESCRIBE_COMMAND: &str = "describe";
+const DISPLAY_NAME_ARG: &str = "display-name";
+
+const KEEP_COMMAND: &str = "keep";
+const OFF_ARG: &str = "off";
+
+const DELETE_BUILD_COMMAND: &str = "delete-build";
+const FORCE_ARG: &str = "force";
+const YES_ARG: &str = "yes";
+
+const SCHEDULER_COMMAND: &str = "scheduler";
+const SCHEDULER_RUN_COMMAND: &str = "run";
+const SCHEDULER_LIST_COMMAND: &str = "list";
+
+const FAV_COMMAND: &str = "fav";
+const FAV_ADD_COMMAND: &str = "add";
+const FAV_REMOVE_COMMAND: &str = "remove";
+
+const CACHE_COMMAND: &str = "cache";
+const CACHE_STATUS_COMMAND: &str = "status";
+const CACHE_REFRESH_COMMAND: &str = "refresh";
+const CACHE_CLEAR_COMMAND: &str = "clear";
+
+const CONFIG_ARG: &str = "config";
+const CONFIG_ENV: &str = "JENCLI_CONFIG";
 
 const EXIT_CODE: i32 = 1;
 
@@ -40,6 +159,28 @@ fn main() {
                 .action(ArgAction::Set)
                 .help("set working directory"),
         )
+        .arg(
+            Arg::new(CONFIG_ARG)
+                .long(CONFIG_ARG)
+                .action(ArgAction::Set)
+                .global(true)
+                .help("config file, default: $JENCLI_CONFIG or $XDG_CONFIG_HOME/jencli/config.yml"),
+        )
+        .arg(
+            Arg::new(REFRESH_ARG)
+                .long(REFRESH_ARG)
+                .action(ArgAction::SetTrue)
+                .global(true)
+                .conflicts_with(NO_CACHE_ARG)
+                .help("fetch data from jenkins and rewrite cache"),
+        )
+        .arg(
+            Arg::new(NO_CACHE_ARG)
+                .long(NO_CACHE_ARG)
+                .action(ArgAction::SetTrue)
+                .global(true)
+                .help("fetch data from jenkins, don't read or write cache"),
+        )
         .subcommand(
             Command::new(LIST_COMMAND)
                 .short_flag('l')
@@ -52,6 +193,62 @@ fn main() {
                         .required(false)
                         .action(ArgAction::Set)
                 )
+                .arg(
+                    Arg::new(STATUS_ARG)
+                        .long(STATUS_ARG)
+                        .action(ArgAction::Append)
+                        .value_parser(["failed", "unstable", "success", "disabled", "running", "never-built"])
+                        .help("filter jobs by status, might be used multiple times"),
+                )
+                .arg(
+                    Arg::new(BUILT_SINCE_ARG)
+                        .long(BUILT_SINCE_ARG)
+                        .value_name("AGE")
+                        .value_parser(|v: &str| parse_duration(v).map_err(|e| e.to_string()))
+                        .help("jobs with last build not older than age, e.g. 7d"),
+                )
+                .arg(
+                    Arg::new(NOT_BUILT_SINCE_ARG)
+                        .long(NOT_BUILT_SINCE_ARG)
+                        .value_name("AGE")
+                        .value_parser(|v: &str| parse_duration(v).map_err(|e| e.to_string()))
+                        .help("jobs without builds or with last build older than age, e.g. 90d"),
+                )
+                .arg(
+                    Arg::new(VIEW_ARG)
+                        .long(VIEW_ARG)
+                        .action(ArgAction::Set)
+                        .help("list jobs of view, nested views are 'parent/child'"),
+                )
+                .arg(
+                    Arg::new(ALL_ARG)
+                        .long(ALL_ARG)
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with(VIEW_ARG)
+                        .help("list all jobs, ignore default view from config"),
+                )
+                .arg(
+                    Arg::new(FAVORITES_ARG)
+                        .long(FAVORITES_ARG)
+                        .action(ArgAction::SetTrue)
+                        .help("list favorite jobs only, default view is ignored"),
+                )
+                .arg(
+                    Arg::new(SORT_ARG)
+                        .long(SORT_ARG)
+                        .value_parser(["name", "last-build", "duration", "status", "recent-use"])
+                        .help("sort jobs, recent-use is based on builds triggered with jencli"),
+                )
+                .arg(
+                    Arg::new(REVERSE_ARG)
+                        .long(REVERSE_ARG)
+                        .action(ArgAction::SetTrue)
+                        .help("reverse order"),
+                )
+        )
+        .subcommand(
+            Command::new(VIEWS_COMMAND)
+                .about("list views, including nested ones")
         )
         .subcommand(
             Command::new(BUILD_COMMAND)
@@ -62,9 +259,410 @@ fn main() {
                     Arg::new(NAME_ARG)
                         .short('n')
                         .long(NAME_ARG)
+                        .action(ArgAction::Append)
+                        .required_unless_present_any([MASK_ARG, STDIN_ARG])
+                        .help("job name, alias or url, might be used multiple times"),
+                )
+                .arg(
+                    Arg::new(MASK_ARG)
+                        .long(MASK_ARG)
+                        .action(ArgAction::Set)
+                        .help("build all jobs matching mask, same as for list command"),
+                )
+                .arg(
+                    Arg::new(STDIN_ARG)
+                        .long(STDIN_ARG)
+                        .action(ArgAction::SetTrue)
+                        .help("read job names from stdin, one per line"),
+                )
+                .arg(
+                    Arg::new(PARALLEL_ARG)
+                        .long(PARALLEL_ARG)
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("1")
+                        .help("max number of jobs triggered (and awaited) at once"),
+                )
+                .arg(
+                    Arg::new(WAIT_ARG)
+                        .long(WAIT_ARG)
+                        .action(ArgAction::SetTrue)
+                        .help("wait for build result and print test summary"),
+                )
+                .arg(
+                    Arg::new(RETRY_ARG)
+                        .long(RETRY_ARG)
+                        .value_parser(clap::value_parser!(u32))
+                        .default_value("0")
+                        .requires(WAIT_ARG)
+                        .help("re-trigger failed build with the same parameters up to N times"),
+                )
+                .arg(
+                    Arg::new(RETRY_ON_ARG)
+                        .long(RETRY_ON_ARG)
+                        .default_value("FAILURE")
+                        .value_name("RESULTS")
+                        .help("comma-separated build results to retry on, e.g. FAILURE,ABORTED"),
+                )
+                .arg(
+                    Arg::new(RETRY_DELAY_ARG)
+                        .long(RETRY_DELAY_ARG)
+                        .value_parser(|v: &str| parse_duration(v).map_err(|e| e.to_string()))
+                        .default_value("30s")
+                        .help("delay before the first retry"),
+                )
+                .arg(
+                    Arg::new(RETRY_BACKOFF_ARG)
+                        .long(RETRY_BACKOFF_ARG)
+                        .value_parser(["fixed", "exponential"])
+                        .default_value("exponential")
+                        .help("fixed delay or doubled after each retry"),
+                )
+                .arg(
+                    Arg::new(IF_IDLE_ARG)
+                        .long(IF_IDLE_ARG)
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with_all([CANCEL_RUNNING_ARG, ALLOW_DUPLICATES_ARG])
+                        .help("skip build if job has queued or running build with identical parameters"),
+                )
+                .arg(
+                    Arg::new(CANCEL_RUNNING_ARG)
+                        .long(CANCEL_RUNNING_ARG)
+                        .action(ArgAction::SetTrue)
+                        .conflicts_with(ALLOW_DUPLICATES_ARG)
+                        .help("abort queued or running build with identical parameters before triggering"),
+                )
+                .arg(get_allow_duplicates_arg())
+                .arg(
+                    Arg::new(DESCRIPTION_ARG)
+                        .long(DESCRIPTION_ARG)
+                        .action(ArgAction::Set)
+                        .value_name("TEXT")
+                        .conflicts_with_all([MASK_ARG, STDIN_ARG])
+                        .help("set build description once build is started, single job only"),
+                )
+                .arg(
+                    Arg::new(DELAY_ARG)
+                        .long(DELAY_ARG)
+                        .value_parser(|v: &str| parse_duration(v).map_err(|e| e.to_string()))
+                        .help("quiet period, build waits in jenkins queue, e.g. 30m"),
+                )
+                .arg(
+                    Arg::new(AT_ARG)
+                        .long(AT_ARG)
+                        .value_name("TIME")
+                        .conflicts_with_all([DELAY_ARG, WAIT_ARG, DESCRIPTION_ARG, RETRY_ARG, IF_IDLE_ARG,
+                                             CANCEL_RUNNING_ARG, ALLOW_DUPLICATES_ARG])
+                        .help("trigger build at local time 'YYYY-MM-DD HH:MM' with `scheduler run`"),
+                )
+        )
+        .subcommand(
+            Command::new(DIFF_COMMAND)
+                .about("compare parameters, changes, duration and tests of two builds")
+                .arg(
+                    Arg::new(JOB_ARG)
+                        .required(true)
+                        .help("job name or url"),
+                )
+                .arg(
+                    Arg::new(FROM_BUILD_ARG)
+                        .required(true)
+                        .value_parser(clap::value_parser!(u32))
+                        .help("older build number"),
+                )
+                .arg(
+                    Arg::new(TO_BUILD_ARG)
+                        .required(true)
+                        .value_parser(clap::value_parser!(u32))
+                        .help("newer build number"),
+                )
+        )
+        .subcommand(
+            Command::new(ARTIFACTS_COMMAND)
+                .about("list or download build artifacts")
+                .arg(
+                    Arg::new(JOB_ARG)
+                        .required(true)
+                        .help("job name or url"),
+                )
+                .arg(
+                    Arg::new(BUILD_ARG)
+                        .required(false)
+                        .help("build number or permalink, lastSuccessfulBuild by default or build of url"),
+                )
+                .arg(
+                    Arg::new(DOWNLOAD_ARG)
+                        .long(DOWNLOAD_ARG)
+                        .num_args(0..=1)
+                        .default_missing_value("")
+                        .help("download artifacts matching glob, all artifacts as zip-archive without glob"),
+                )
+                .arg(
+                    Arg::new(DEST_ARG)
+                        .long(DEST_ARG)
+                        .default_value(".")
+                        .help("destination directory for downloads"),
+                )
+        )
+        .subcommand(
+            Command::new(TESTS_COMMAND)
+                .about("show test report summary for build")
+                .arg(
+                    Arg::new(JOB_ARG)
+                        .required(true)
+                        .help("job name or url"),
+                )
+                .arg(
+                    Arg::new(BUILD_ARG)
+                        .required(false)
+                        .help("build number or permalink, lastBuild by default or build of url"),
+                )
+                .arg(
+                    Arg::new(JUNIT_ARG)
+                        .long(JUNIT_ARG)
+                        .action(ArgAction::Set)
+                        .help("export test report into junit xml file"),
+                )
+        )
+        .subcommand(
+            Command::new(STAGES_COMMAND)
+                .about("show pipeline stages for build")
+                .arg(
+                    Arg::new(JOB_ARG)
+                        .required(true)
+                        .help("job name or url"),
+                )
+                .arg(
+                    Arg::new(BUILD_ARG)
+                        .required(false)
+                        .help("build number or permalink, lastBuild by default or build of url"),
+                )
+                .arg(
+                    Arg::new(LOG_ARG)
+                        .long(LOG_ARG)
+                        .action(ArgAction::Set)
+                        .value_name("STAGE")
+                        .help("print logs of stage"),
+                )
+        )
+        .subcommand(
+            Command::new(INPUT_COMMAND)
+                .about("list, proceed or abort pending pipeline input steps")
+                .arg(
+                    Arg::new(JOB_ARG)
+                        .required(true)
+                        .help("job name or url"),
+                )
+                .arg(
+                    Arg::new(BUILD_ARG)
+                        .required(false)
+                        .help("build number or permalink, lastBuild by default or build of url"),
+                )
+                .arg(
+                    Arg::new(PROCEED_ARG)
+                        .long(PROCEED_ARG)
+                        .num_args(0..=1)
+                        .default_missing_value("")
+                        .value_name("ID")
+                        .conflicts_with(ABORT_ARG)
+                        .help("proceed input, id is optional if there's single pending input"),
+                )
+                .arg(
+                    Arg::new(ABORT_ARG)
+                        .long(ABORT_ARG)
+                        .num_args(0..=1)
+                        .default_missing_value("")
+                        .value_name("ID")
+                        .help("abort input, id is optional if there's single pending input"),
+                )
+                .arg(
+                    Arg::new(PARAM_ARG)
+                        .short('p')
+                        .long(PARAM_ARG)
+                        .action(ArgAction::Append)
+                        .value_name("NAME=VALUE")
+                        .help("input parameter value"),
+                )
+        )
+        .subcommand(
+            Command::new(REPLAY_COMMAND)
+                .about("replay pipeline build with modified scripts")
+                .arg(
+                    Arg::new(JOB_ARG)
+                        .required(true)
+                        .help("job name or url"),
+                )
+                .arg(
+                    Arg::new(BUILD_ARG)
+                        .help("build number or permalink, might be omitted for build url"),
+                )
+                .arg(
+                    Arg::new(SCRIPT_ARG)
+                        .long(SCRIPT_ARG)
+                        .action(ArgAction::Set)
+                        .value_name("PATH")
+                        .help("main script (Jenkinsfile) for replay"),
+                )
+                .arg(
+                    Arg::new(LOADED_SCRIPT_ARG)
+                        .long(LOADED_SCRIPT_ARG)
+                        .action(ArgAction::Append)
+                        .value_name("NAME=PATH")
+                        .help("loaded script for replay"),
+                )
+                .arg(
+                    Arg::new(FETCH_ARG)
+                        .long(FETCH_ARG)
+                        .action(ArgAction::Set)
+                        .value_name("DIR")
+                        .conflicts_with_all([SCRIPT_ARG, LOADED_SCRIPT_ARG])
+                        .help("save current scripts into directory for local edits"),
+                )
+        )
+        .subcommand(
+            Command::new(RESTART_COMMAND)
+                .about("restart declarative pipeline from stage")
+                .arg(
+                    Arg::new(JOB_ARG)
+                        .required(true)
+                        .help("job name or url"),
+                )
+                .arg(
+                    Arg::new(BUILD_ARG)
+                        .help("build number or permalink, might be omitted for build url"),
+                )
+                .arg(
+                    Arg::new(STAGE_ARG)
+                        .long(STAGE_ARG)
+                        .action(ArgAction::Set)
+                        .help("stage name, restartable stages are listed without it"),
+                )
+        )
+        .subcommand(
+            Command::new(RUN_COMMAND)
+                .about("run build chain described in yaml file")
+                .arg(
+                    Arg::new(PLAN_ARG)
+                        .required(true)
+                        .help("path to plan file"),
+                )
+                .arg(get_allow_duplicates_arg())
+        )
+        .subcommand(
+            Command::new(DESCRIBE_COMMAND)
+                .about("set build description and display name")
+                .arg(
+                    Arg::new(JOB_ARG)
+                        .required(true)
+                        .help("job name or url"),
+                )
+                .arg(
+                    Arg::new(BUILD_ARG)
+                        .help("build number or permalink, might be omitted for build url"),
+                )
+                .arg(
+                    Arg::new(DESCRIPTION_ARG)
+                        .long(DESCRIPTION_ARG)
                         .action(ArgAction::Set)
+                        .value_name("TEXT")
+                        .required_unless_present(DISPLAY_NAME_ARG)
+                        .help("build description, html is allowed if enabled on server"),
+                )
+                .arg(
+                    Arg::new(DISPLAY_NAME_ARG)
+                        .long(DISPLAY_NAME_ARG)
+                        .action(ArgAction::Set)
+                        .value_name("TEXT")
+                        .help("build display name, shown instead of build number"),
+                )
+        )
+        .subcommand(
+            Command::new(KEEP_COMMAND)
+                .about("keep build forever, so it isn't removed by log rotation")
+                .arg(
+                    Arg::new(JOB_ARG)
+                        .required(true)
+                        .help("job name or url"),
+                )
+                .arg(
+                    Arg::new(BUILD_ARG)
+                        .help("build number or permalink, might be omitted for build url"),
+                )
+                .arg(
+                    Arg::new(OFF_ARG)
+                        .long(OFF_ARG)
+                        .action(ArgAction::SetTrue)
+                        .help("don't keep build forever anymore"),
+                )
+        )
+        .subcommand(
+            Command::new(DELETE_BUILD_COMMAND)
+                .about("delete builds, kept forever and running builds are skipped")
+                .arg(
+                    Arg::new(JOB_ARG)
                         .required(true)
-                        .help("job name"),
+                        .help("job name or url"),
+                )
+                .arg(
+                    Arg::new(BUILD_ARG)
+                        .help("build number or range, e.g. 40-45, might be omitted for build url"),
+                )
+                .arg(
+                    Arg::new(FORCE_ARG)
+                        .long(FORCE_ARG)
+                        .action(ArgAction::SetTrue)
+                        .help("delete kept forever builds too"),
+                )
+                .arg(
+                    Arg::new(YES_ARG)
+                        .long(YES_ARG)
+                        .action(ArgAction::SetTrue)
+                        .help("don't ask for confirmation, required without terminal"),
+                )
+        )
+        .subcommand(
+            Command::new(SCHEDULER_COMMAND)
+                .about("builds scheduled with `build --at`")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new(SCHEDULER_RUN_COMMAND)
+                        .about("trigger builds which time has come, e.g. from cron")
+                        .arg(get_allow_duplicates_arg())
+                )
+                .subcommand(
+                    Command::new(SCHEDULER_LIST_COMMAND)
+                        .about("list pending builds")
+                )
+        )
+        .subcommand(
+            Command::new(FAV_COMMAND)
+                .about("edit favorite jobs in config")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new(FAV_ADD_COMMAND)
+                        .about("add job to favorites")
+                        .arg(Arg::new(JOB_ARG).required(true).help("job name or alias"))
+                )
+                .subcommand(
+                    Command::new(FAV_REMOVE_COMMAND)
+                        .about("remove job from favorites")
+                        .arg(Arg::new(JOB_ARG).required(true).help("job name or alias"))
+                )
+        )
+        .subcommand(
+            Command::new(CACHE_COMMAND)
+                .about("manage local cache")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new(CACHE_STATUS_COMMAND)
+                        .about("show cache age, size and entries")
+                )
+                .subcommand(
+                    Command::new(CACHE_REFRESH_COMMAND)
+                        .about("fetch data from jenkins and rewrite cache")
+                )
+                .subcommand(
+                    Command::new(CACHE_CLEAR_COMMAND)
+                        .about("remove cached data")
                 )
         )
         .get_matches();
@@ -75,111 +673,1131 @@ fn main() {
 
     match matches.subcommand() {
         Some((LIST_COMMAND, list_matches)) => {
-            let config_file_path = Path::new("config.yml");
+            let config = load_config(&matches);
+            let client = get_http_client();
 
-            let config = load_config_from_file(&config_file_path)
-                .expect("unable to load config from file");
+            let status_filter = get_job_status_filter(list_matches);
 
-            let client = ClientBuilder::new().build()
-                .expect("unable to build http client");
+            let favorites = list_matches.get_flag(FAVORITES_ARG);
 
-            match get_jenkins_job_list(&client, &config.jenkins_url,
-                                       &config.username, &config.token) {
-                Ok(job_list) => {
+            let view = match list_matches.get_flag(ALL_ARG) || favorites {
+                true => list_matches.get_one::<String>(VIEW_ARG),
+                false => list_matches.get_one::<String>(VIEW_ARG).or(config.list.default_view.as_ref())
+            };
 
-                    let job_list = job_list.into_iter()
-                        .filter(|j|
-                            !config.list.exclude.iter()
-                            .find(|e|j.name.contains(*e)).is_some())
-                        .collect::<Vec<JenkinsJob>>();
+            let sort = list_matches.get_one::<String>(SORT_ARG).map(|s| match s.as_str() {
+                "name" => JobSort::Name,
+                "last-build" => JobSort::LastBuild,
+                "duration" => JobSort::Duration,
+                "status" => JobSort::Status,
+                _ => JobSort::RecentUse
+            });
 
-                    let json = if let Some(mask) = list_matches
-                        .get_one::<String>(MASK_ARG) {
+            // Favorites might be inside folders, so they are taken from config unless view is passed
+            let favorite_jobs_only = favorites && view.is_none();
 
-                        let mask = mask.to_lowercase();
+            let job_list = if !status_filter.is_empty() || sort.is_some_and(|s| s.requires_status()) {
+                let now = Local::now().timestamp_millis() as u64;
 
-                        let job_list = job_list.into_iter()
-                            .filter(|j|
-                            j.name.to_lowercase()
-                            .contains(&mask)).collect::<Vec<JenkinsJob>>();
+                let jobs = match favorite_jobs_only {
+                    true => config.favorites.iter()
+                        .map(|name| get_jenkins_job_status(&client, &config.jenkins_url, config.get_username(),
+                                                           config.get_token(), name))
+                        .collect::<anyhow::Result<Vec<JenkinsJobStatus>>>(),
+                    false => get_jenkins_job_statuses(&client, &config.jenkins_url, config.get_username(),
+                                                      config.get_token(), view.map(|v| v.as_str()))
+                };
 
-                        serde_json::to_string(&job_list)
-                            .expect("unable to serialize results")
+                jobs.map(|jobs| {
+                    let mut jobs = jobs.into_iter()
+                        .filter(|j| status_filter.matches(j, now))
+                        .collect::<Vec<JenkinsJobStatus>>();
 
-                    } else {
+                    if let Some(sort) = sort {
+                        sort_job_statuses(&mut jobs, sort);
+                    }
 
-                        serde_json::to_string(&job_list)
-                            .expect("unable to serialize results")
-                    };
+                    jobs.into_iter().map(|j| JenkinsJob { name: j.name, url: j.url }).collect()
+                })
 
-                    println!("{json}");
+            } else if favorite_jobs_only {
+                Ok(get_favorite_job_list(&config.jenkins_url, &config.favorites))
 
+            } else if let Some(view) = view {
+                get_view_job_list(&client, &config.jenkins_url, config.get_username(), config.get_token(), view)
+
+            } else {
+                get_jenkins_job_list(&client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                     &config.cache)
+            };
+
+            match job_list {
+                Ok(job_list) => {
+                    let mut job_list = filter_job_list(job_list, &config.list.exclude,
+                                                       list_matches.get_one::<String>(MASK_ARG));
+
+                    if favorites {
+                        job_list.retain(|j| config.favorites.contains(&j.name));
+                    }
+
+                    if let Some(sort) = sort {
+                        let usage = load_job_usage(&config.jenkins_url).unwrap_or_else(|e| exit_with_error(e));
+                        sort_job_list(&mut job_list, sort, &usage);
+                    }
+
+                    if list_matches.get_flag(REVERSE_ARG) {
+                        job_list.reverse();
+                    }
+
+                    let json = serde_json::to_string(&job_list)
+                        .expect("unable to serialize results");
+
+                    println!("{json}");
                 }
-                Err(e) => {
-                    eprintln!("error: {}", e);
-                    eprintln!("{}", e.root_cause());
-                    exit(EXIT_CODE);
-                }
+                Err(e) => exit_with_error(e)
             }
         }
-        Some((BUILD_COMMAND, rebuild_matches)) => {
-            if let Some(job_name) = rebuild_matches.get_one::<String>(NAME_ARG) {
-                println!("rebuilding job '{NAME_ARG}'...");
+        Some((FAV_COMMAND, fav_matches)) => {
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            let mut favorites = config.favorites.clone();
 
-                let config_file_path = Path::new("config.yml");
+            match fav_matches.subcommand() {
+                Some((FAV_ADD_COMMAND, add_matches)) => {
+                    let job_name = get_job_name(&client, &config, add_matches.get_one::<String>(JOB_ARG).unwrap());
 
-                let config = load_config_from_file(&config_file_path)
-                    .expect("unable to load config from file");
+                    if favorites.contains(&job_name) {
+                        println!("job '{job_name}' is already in favorites");
+                        return
+                    }
 
-                let client = ClientBuilder::new().build()
-                    .expect("unable to build http client");
+                    favorites.push(job_name.to_string());
+                    println!("job '{job_name}' added to favorites");
+                }
+                Some((FAV_REMOVE_COMMAND, remove_matches)) => {
+                    let job_name = config.resolve_alias(remove_matches.get_one::<String>(JOB_ARG).unwrap());
 
-                match build_job(&client, &config.jenkins_url, &config.username, &config.token, job_name) {
-                    Ok(_) => println!("rebuild successfully executed"),
-                    Err(e) => {
-                        eprintln!("error: {}", e);
-                        eprintln!("{}", e.root_cause());
+                    if !favorites.contains(&job_name) {
+                        eprintln!("job '{job_name}' isn't in favorites");
                         exit(EXIT_CODE);
                     }
-                }
 
-            } else {
-                eprintln!("Required '{NAME_ARG}' argument");
-                exit(EXIT_CODE);
+                    favorites.retain(|f| f != &job_name);
+                    println!("job '{job_name}' removed from favorites");
+                }
+                _ => {}
             }
+
+            save_favorites_to_file(&get_config_path(&matches), &favorites)
+                .unwrap_or_else(|e| exit_with_error(e));
         }
-        _ => {}
-    }
-}
+        Some((CACHE_COMMAND, cache_matches)) => {
+            match cache_matches.subcommand() {
+                Some((CACHE_STATUS_COMMAND, _)) => {
+                    // Only cache ttl is used, credentials aren't required
+                    let config = load_config_file(&matches);
 
-fn init_logging(logging_level: &str) -> anyhow::Result<()> {
-    let logging_config = get_logging_config(logging_level);
-    log4rs::init_config(logging_config).context("unable to init logging subsystem")?;
-    Ok(())
-}
+                    let statuses = get_cache_status().unwrap_or_else(|e| exit_with_error(e));
 
-fn init_working_dir(matches: &ArgMatches) {
-    let working_directory: &Path = get_argument_path_value(
-        &matches, WORK_DIR_ARG, WORK_DIR_SHORT_ARG, ".");
+                    if statuses.is_empty() {
+                        println!("cache is empty");
+                        return
+                    }
 
-    info!("working directory '{}'", &working_directory.display());
+                    let now = Local::now().timestamp();
 
-    env::set_current_dir(&working_directory).expect("couldn't set working directory");
-}
+                    for status in statuses {
+                        let age = Duration::from_secs(now.saturating_sub(status.fetched_at) as u64);
 
-fn get_argument_path_value<'a>(matches: &'a ArgMatches, long_argument: &str,
-                               short_argument: char, default_path: &'a str) -> &'a Path {
-    let mut path: &Path = Path::new(default_path);
+                        let stale = match age >= config.cache.ttl {
+                            true => " (stale)",
+                            false => ""
+                        };
 
-    match matches.get_one::<String>(long_argument) {
-        Some(value) => path = Path::new(value),
-        None => {}
-    }
+                        println!("{}  {}  {}  {} entries  {}  age {}{stale}", status.jenkins_url, status.username,
+                                 status.cache_type, status.entries, format_size(status.size),
+                                 format_duration(age.as_millis() as u64));
+                    }
+                }
+                Some((CACHE_REFRESH_COMMAND, _)) => {
+                    let mut config = load_config(&matches);
+                    config.cache.mode = CacheMode::Refresh;
 
-    match matches.get_one::<String>(&short_argument.to_string()) {
-        Some(value) => path = Path::new(value),
-        None => {}
-    }
+                    let client = get_http_client();
 
-    path
+                    match get_jenkins_job_list(&client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                               &config.cache) {
+                        Ok(jobs) => println!("job list cache refreshed, {} jobs", jobs.len()),
+                        Err(e) => exit_with_error(e)
+                    }
+                }
+                Some((CACHE_CLEAR_COMMAND, _)) => {
+                    let removed = clear_cache().unwrap_or_else(|e| exit_with_error(e));
+                    println!("cache cleared, {removed} files removed");
+                }
+                _ => {}
+            }
+        }
+        Some((VIEWS_COMMAND, _)) => {
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            match get_jenkins_views(&client, &config.jenkins_url, config.get_username(), config.get_token()) {
+                Ok(views) => views.iter().for_each(|v| println!("{v}")),
+                Err(e) => exit_with_error(e)
+            }
+        }
+        Some((BUILD_COMMAND, rebuild_matches)) => {
+            let config = load_build_config(&matches);
+            let client = get_http_client();
+
+            if !config.has_credentials() {
+                let options = [WAIT_ARG, IF_IDLE_ARG, CANCEL_RUNNING_ARG, DESCRIPTION_ARG, MASK_ARG];
+
+                if let Some(option) = options.iter().find(|o| rebuild_matches.value_source(o) == Some(ValueSource::CommandLine)) {
+                    eprintln!("--{option} requires username and token in config");
+                    exit(EXIT_CODE);
+                }
+            }
+
+            let at = rebuild_matches.get_one::<String>(AT_ARG)
+                .map(|at| parse_schedule_time(at, Local::now().timestamp()).unwrap_or_else(|e| exit_with_error(e)));
+
+            let job_names = get_build_job_names(rebuild_matches, &client, &config);
+
+            if let Some(job_name) = job_names.iter().find(|j| !can_build_job(&config, j)) {
+                eprintln!("job '{job_name}' doesn't have trigger token, username and token are required in config");
+                exit(EXIT_CODE);
+            }
+
+            let wait = rebuild_matches.get_flag(WAIT_ARG);
+
+            let retry = get_retry_policy(rebuild_matches);
+
+            let duplicates = if rebuild_matches.get_flag(IF_IDLE_ARG) {
+                DuplicateBuildPolicy::Skip
+
+            } else if rebuild_matches.get_flag(CANCEL_RUNNING_ARG) {
+                DuplicateBuildPolicy::CancelRunning
+
+            } else {
+                get_duplicate_build_policy(rebuild_matches)
+            };
+
+            let description = rebuild_matches.get_one::<String>(DESCRIPTION_ARG);
+
+            let delay = rebuild_matches.get_one::<Duration>(DELAY_ARG).copied();
+
+            // Output and exit code don't depend on how many jobs are matched
+            let bulk = rebuild_matches.get_one::<String>(MASK_ARG).is_some() || rebuild_matches.get_flag(STDIN_ARG);
+
+            if let Some(at) = at {
+                for job_name in &job_names {
+                    schedule_build(&config.jenkins_url, job_name, &at).unwrap_or_else(|e| exit_with_error(e));
+                    println!("build for job '{job_name}' scheduled at {}", at.format("%Y-%m-%d %H:%M"));
+                }
+
+                return
+            }
+
+            match job_names.as_slice() {
+                [] => {
+                    eprintln!("no jobs to build");
+                    exit(EXIT_CODE);
+                }
+                [_, _, ..] if description.is_some() => {
+                    eprintln!("description is supported for single job only");
+                    exit(EXIT_CODE);
+                }
+                [job_name] if !bulk => {
+                    let duplicates = confirm_single_build(&client, &config, job_name, duplicates);
+
+                    println!("rebuilding job '{job_name}'...");
+
+                    let description = description.map(|d| d.as_str());
+
+                    let outcome = match wait {
+                        true => trigger_build_with_retry(&client, &config.jenkins_url, config.get_username(),
+                                                         config.get_token(), job_name, &HashMap::new(), &retry,
+                                                         duplicates, delay, description, &config.trigger_tokens),
+                        false => trigger_build(&client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                               job_name, &HashMap::new(), false, duplicates, delay, description,
+                                               &config.trigger_tokens)
+                    };
+
+                    record_usage(&config, slice::from_ref(&outcome));
+
+                    if wait {
+                        if let Some(number) = outcome.number {
+                            print_test_summary(&client, &config, job_name, number);
+                        }
+
+                        println!("{}", BuildSummary(slice::from_ref(&outcome)));
+
+                    } else if let Some(error) = &outcome.error {
+                        eprintln!("error: {error}");
+                    }
+
+                    if outcome.is_failed() {
+                        exit(EXIT_CODE);
+                    }
+                }
+                _ => {
+                    let parallel = *rebuild_matches.get_one::<usize>(PARALLEL_ARG).unwrap();
+
+                    let outcomes = trigger_builds(&client, &config.jenkins_url, config.get_username(),
+                                                  config.get_token(), &job_names, parallel, wait, &retry, duplicates,
+                                                  delay, &config.trigger_tokens);
+
+                    record_usage(&config, &outcomes);
+
+                    println!("{}", BuildSummary(&outcomes));
+
+                    if outcomes.iter().any(|o| o.is_failed()) {
+                        exit(EXIT_CODE);
+                    }
+                }
+            }
+        }
+        Some((DIFF_COMMAND, diff_matches)) => {
+            let from = *diff_matches.get_one::<u32>(FROM_BUILD_ARG).unwrap();
+            let to = *diff_matches.get_one::<u32>(TO_BUILD_ARG).unwrap();
+
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            let job_name = &get_job_name(&client, &config, diff_matches.get_one::<String>(JOB_ARG).unwrap());
+
+            match get_builds_diff(&client, &config.jenkins_url, config.get_username(),
+                                  config.get_token(), job_name, from, to) {
+                Ok(diff) => println!("{diff}"),
+                Err(e) => exit_with_error(e)
+            }
+        }
+        Some((ARTIFACTS_COMMAND, artifacts_matches)) => {
+            let build = &get_build(artifacts_matches, Some("lastSuccessfulBuild"));
+
+            let config = load_config(&matches);
+
+            let client = ClientBuilder::new().timeout(None).build()
+                .expect("unable to build http client");
+
+            let job_name = &get_job_name(&client, &config, artifacts_matches.get_one::<String>(JOB_ARG).unwrap());
+
+            let build_artifacts = get_build_artifacts(&client, &config.jenkins_url, config.get_username(),
+                                                      config.get_token(), job_name, build)
+                .unwrap_or_else(|e| exit_with_error(e));
+
+            match artifacts_matches.get_one::<String>(DOWNLOAD_ARG) {
+                Some(glob) => {
+                    let dest = Path::new(artifacts_matches.get_one::<String>(DEST_ARG).unwrap());
+
+                    if glob.is_empty() {
+                        match download_artifacts_archive(&client, &config.jenkins_url, config.get_username(),
+                                                         config.get_token(), job_name, build_artifacts.number, dest) {
+                            Ok(path) => println!("{}", path.display()),
+                            Err(e) => exit_with_error(e)
+                        }
+
+                    } else {
+                        let artifacts = build_artifacts.artifacts.iter()
+                            .filter(|a| matches_glob(glob, &a.relative_path))
+                            .collect::<Vec<_>>();
+
+                        if artifacts.is_empty() {
+                            eprintln!("no artifacts match '{glob}' in build #{}", build_artifacts.number);
+                            exit(EXIT_CODE);
+                        }
+
+                        for artifact in artifacts {
+                            match download_artifact(&client, &config.jenkins_url, config.get_username(),
+                                                    config.get_token(), job_name, build_artifacts.number,
+                                                    artifact, dest) {
+                                Ok(path) => println!("{}", path.display()),
+                                Err(e) => exit_with_error(e)
+                            }
+                        }
+                    }
+                }
+                None => {
+                    println!("build #{}:", build_artifacts.number);
+
+                    build_artifacts.artifacts.iter()
+                        .for_each(|a| println!("  {}", a.relative_path));
+                }
+            }
+        }
+        Some((TESTS_COMMAND, tests_matches)) => {
+            let build = &get_build(tests_matches, Some("lastBuild"));
+
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            let job_name = &get_job_name(&client, &config, tests_matches.get_one::<String>(JOB_ARG).unwrap());
+
+            match get_test_report(&client, &config.jenkins_url, config.get_username(),
+                                  config.get_token(), job_name, build) {
+                Ok(Some(report)) => {
+                    println!("{report}");
+
+                    if let Some(junit_file) = tests_matches.get_one::<String>(JUNIT_ARG) {
+                        export_test_report_to_junit(&report, Path::new(junit_file))
+                            .unwrap_or_else(|e| exit_with_error(e));
+                    }
+                }
+                Ok(None) => println!("build '{build}' doesn't have test report"),
+                Err(e) => exit_with_error(e)
+            }
+        }
+        Some((STAGES_COMMAND, stages_matches)) => {
+            let build = &get_build(stages_matches, Some("lastBuild"));
+
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            let job_name = &get_job_name(&client, &config, stages_matches.get_one::<String>(JOB_ARG).unwrap());
+
+            let run = get_pipeline_run(&client, &config.jenkins_url, config.get_username(),
+                                       config.get_token(), job_name, build)
+                .unwrap_or_else(|e| exit_with_error(e));
+
+            match stages_matches.get_one::<String>(LOG_ARG) {
+                Some(stage_name) => match run.find_stage(stage_name) {
+                    Some(stage) => match get_stage_log(&client, &config.jenkins_url,
+                                                       config.get_username(), config.get_token(), stage) {
+                        Ok(log) => println!("{log}"),
+                        Err(e) => exit_with_error(e)
+                    },
+                    None => {
+                        eprintln!("stage '{stage_name}' not found, available stages: {}",
+                                  run.stages.iter().map(|s| s.name.as_str()).collect::<Vec<&str>>().join(", "));
+                        exit(EXIT_CODE);
+                    }
+                }
+                None => println!("{run}")
+            }
+        }
+        Some((INPUT_COMMAND, input_matches)) => {
+            let build = &get_build(input_matches, Some("lastBuild"));
+
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            let job_name = &get_job_name_to_modify(&client, &config, input_matches.get_one::<String>(JOB_ARG).unwrap());
+
+            let actions = get_pending_input_actions(&client, &config.jenkins_url, config.get_username(),
+                                                    config.get_token(), job_name, build)
+                .unwrap_or_else(|e| exit_with_error(e));
+
+            if let Some(input_id) = input_matches.get_one::<String>(PROCEED_ARG) {
+                let action = find_input_action(&actions, input_id);
+
+                let values = get_param_values(input_matches);
+
+                match proceed_input(&client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                    job_name, build, action, &values) {
+                    Ok(_) => println!("input '{}' proceeded", action.id),
+                    Err(e) => exit_with_error(e)
+                }
+
+            } else if let Some(input_id) = input_matches.get_one::<String>(ABORT_ARG) {
+                let action = find_input_action(&actions, input_id);
+
+                match abort_input(&client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                  job_name, build, action) {
+                    Ok(_) => println!("input '{}' aborted", action.id),
+                    Err(e) => exit_with_error(e)
+                }
+
+            } else if actions.is_empty() {
+                println!("no pending input actions");
+
+            } else {
+                actions.iter().for_each(|a| println!("{a}"));
+            }
+        }
+        Some((REPLAY_COMMAND, replay_matches)) => {
+            let build = &get_build(replay_matches, None);
+
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            let job_name = &get_job_name_to_modify(&client, &config, replay_matches.get_one::<String>(JOB_ARG).unwrap());
+
+            let mut scripts = get_replay_scripts(&client, &config.jenkins_url, config.get_username(),
+                                                 config.get_token(), job_name, build)
+                .unwrap_or_else(|e| exit_with_error(e));
+
+            let script_path = replay_matches.get_one::<String>(SCRIPT_ARG);
+            let loaded_script_paths = replay_matches.get_many::<String>(LOADED_SCRIPT_ARG);
+
+            if let Some(dir) = replay_matches.get_one::<String>(FETCH_ARG) {
+                let dir = Path::new(dir);
+                fs::create_dir_all(dir).expect("unable to create directory");
+
+                let mut files = vec![(dir.join(MAIN_SCRIPT_FILE), &scripts.main_script)];
+
+                scripts.loaded_scripts.iter()
+                    .for_each(|(name, script)| files.push((dir.join(format!("{name}.groovy")), script)));
+
+                for (file_path, script) in files {
+                    fs::write(&file_path, script).expect("unable to write script file");
+                    println!("{}", file_path.display());
+                }
+
+            } else if script_path.is_none() && loaded_script_paths.is_none() {
+                println!("{}", scripts.main_script);
+
+                if !scripts.loaded_scripts.is_empty() {
+                    eprintln!("loaded scripts: {}",
+                              scripts.loaded_scripts.keys().cloned().collect::<Vec<String>>().join(", "));
+                }
+
+            } else {
+                if let Some(script_path) = script_path {
+                    scripts.main_script = read_script_file(script_path);
+                }
+
+                for loaded_script in loaded_script_paths.unwrap_or_default() {
+                    match loaded_script.split_once('=') {
+                        Some((name, path)) => scripts.set_loaded_script(name, read_script_file(path))
+                            .unwrap_or_else(|e| exit_with_error(e)),
+                        None => {
+                            eprintln!("invalid loaded script '{loaded_script}', expected format NAME=PATH");
+                            exit(EXIT_CODE);
+                        }
+                    }
+                }
+
+                match replay_build(&client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                   job_name, build, &scripts) {
+                    Ok(_) => println!("replay of build '{build}' for job '{job_name}' has been submitted"),
+                    Err(e) => exit_with_error(e)
+                }
+            }
+        }
+        Some((RESTART_COMMAND, restart_matches)) => {
+            let build = &get_build(restart_matches, None);
+
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            let job_name = &get_job_name_to_modify(&client, &config, restart_matches.get_one::<String>(JOB_ARG).unwrap());
+
+            let stages = get_restartable_stages(&client, &config.jenkins_url, config.get_username(),
+                                                config.get_token(), job_name, build)
+                .unwrap_or_else(|e| exit_with_error(e));
+
+            if stages.is_empty() {
+                eprintln!("build '{build}' for job '{job_name}' isn't eligible for restart");
+                exit(EXIT_CODE);
+            }
+
+            match restart_matches.get_one::<String>(STAGE_ARG) {
+                Some(stage) => {
+                    if !stages.contains(stage) {
+                        eprintln!("stage '{stage}' can't be restarted, restartable stages: {}", stages.join(", "));
+                        exit(EXIT_CODE);
+                    }
+
+                    match restart_from_stage(&client, &config.jenkins_url, config.get_username(),
+                                             config.get_token(), job_name, build, stage) {
+                        Ok(_) => println!("build '{build}' for job '{job_name}' restarted from stage '{stage}'"),
+                        Err(e) => exit_with_error(e)
+                    }
+                }
+                None => stages.iter().for_each(|s| println!("{s}"))
+            }
+        }
+        Some((RUN_COMMAND, run_matches)) => {
+            let plan_path = Path::new(run_matches.get_one::<String>(PLAN_ARG).unwrap());
+
+            let mut plan = load_plan_from_file(plan_path).unwrap_or_else(|e| exit_with_error(e));
+
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            let mut job_list = None;
+
+            for step in plan.steps.iter_mut() {
+                step.job = resolve_job_to_modify(&client, &config, &step.job, &mut job_list)
+                    .unwrap_or_else(|e| exit_with_resolve_error(e));
+            }
+
+            let report = run_plan(&client, &config.jenkins_url, config.get_username(), config.get_token(), &plan,
+                                  get_duplicate_build_policy(run_matches), &config.trigger_tokens);
+
+            report.steps.iter()
+                .filter(|(_, outcome)| outcome.is_triggered())
+                .for_each(|(_, outcome)| record_job_usage_of(&config, &outcome.job_name));
+
+            println!("{report}");
+
+            if report.is_failed() {
+                exit(EXIT_CODE);
+            }
+        }
+        Some((DESCRIBE_COMMAND, describe_matches)) => {
+            let build = &get_build(describe_matches, None);
+            let description = describe_matches.get_one::<String>(DESCRIPTION_ARG);
+
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            let job_name = &get_job_name_to_modify(&client, &config, describe_matches.get_one::<String>(JOB_ARG).unwrap());
+
+            let result = match describe_matches.get_one::<String>(DISPLAY_NAME_ARG) {
+                Some(display_name) => set_build_display_name(&client, &config.jenkins_url, config.get_username(),
+                                                             config.get_token(), job_name, build, display_name,
+                                                             description.map(|d| d.as_str())),
+                None => set_build_description(&client, &config.jenkins_url, config.get_username(),
+                                              config.get_token(), job_name, build, description.unwrap())
+            };
+
+            match result {
+                Ok(_) => println!("build '{build}' for job '{job_name}' updated"),
+                Err(e) => exit_with_error(e)
+            }
+        }
+        Some((KEEP_COMMAND, keep_matches)) => {
+            let build = &get_build(keep_matches, None);
+            let keep = !keep_matches.get_flag(OFF_ARG);
+
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            let job_name = &get_job_name_to_modify(&client, &config, keep_matches.get_one::<String>(JOB_ARG).unwrap());
+
+            let state = match get_build_state(&client, &config.jenkins_url, config.get_username(),
+                                              config.get_token(), job_name, build) {
+                Ok(Some(state)) => state,
+                Ok(None) => {
+                    eprintln!("build '{build}' for job '{job_name}' not found");
+                    exit(EXIT_CODE);
+                }
+                Err(e) => exit_with_error(e)
+            };
+
+            if state.keep_log != keep {
+                toggle_keep_build(&client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                  job_name, &state.number.to_string())
+                    .unwrap_or_else(|e| exit_with_error(e));
+            }
+
+            match keep {
+                true => println!("build #{} for job '{job_name}' is kept forever", state.number),
+                false => println!("build #{} for job '{job_name}' isn't kept forever", state.number)
+            }
+        }
+        Some((DELETE_BUILD_COMMAND, delete_matches)) => {
+            let force = delete_matches.get_flag(FORCE_ARG);
+            let yes = delete_matches.get_flag(YES_ARG);
+
+            if !yes && !io::stdin().is_terminal() {
+                eprintln!("builds aren't deleted without terminal, use --{YES_ARG} to confirm");
+                exit(EXIT_CODE);
+            }
+
+            let range = parse_build_range(&get_build(delete_matches, None))
+                .unwrap_or_else(|e| exit_with_error(e));
+
+            let config = load_config(&matches);
+            let client = get_http_client();
+
+            let job_name = &get_job_name_to_modify(&client, &config, delete_matches.get_one::<String>(JOB_ARG).unwrap());
+
+            if is_job_excluded(job_name, &config.list.exclude) {
+                eprintln!("job '{job_name}' is excluded in config (list.exclude), builds can't be deleted");
+                exit(EXIT_CODE);
+            }
+
+            let mut builds: Vec<JenkinsBuildState> = vec![];
+
+            for number in range {
+                match get_build_state(&client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                      job_name, &number.to_string()) {
+                    Ok(Some(state)) if state.is_protected_with(force) => match state.building {
+                        true => println!("build #{number} is running, skipped"),
+                        false => println!("build #{number} is kept forever, skipped")
+                    },
+                    Ok(Some(state)) => builds.push(state),
+                    Ok(None) => info!("build #{number} not found"),
+                    Err(e) => exit_with_error(e)
+                }
+            }
+
+            if builds.is_empty() {
+                eprintln!("no builds to delete");
+                exit(EXIT_CODE);
+            }
+
+            let numbers = builds.iter().map(|b| format!("#{}", b.number)).collect::<Vec<String>>().join(", ");
+
+            if !yes && !confirm(&format!("delete builds {numbers} for job '{job_name}'?")) {
+                exit(EXIT_CODE);
+            }
+
+            for build in builds {
+                let number = build.number.to_string();
+
+                let result = match build.keep_log {
+                    true => toggle_keep_build(&client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                              job_name, &number)
+                        .and_then(|_| delete_build(&client, &config.jenkins_url, config.get_username(),
+                                                   config.get_token(), job_name, &number)),
+                    false => delete_build(&client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                          job_name, &number)
+                };
+
+                match result {
+                    Ok(_) => println!("build #{number} deleted"),
+                    Err(e) => exit_with_error(e)
+                }
+            }
+        }
+        Some((SCHEDULER_COMMAND, scheduler_matches)) => {
+            let builds = load_scheduled_builds().unwrap_or_else(|e| exit_with_error(e));
+
+            match scheduler_matches.subcommand() {
+                Some((SCHEDULER_RUN_COMMAND, run_matches)) => {
+                    let config = load_build_config(&matches);
+
+                    // Builds of other servers stay pending
+                    let (due, pending) = split_due_builds(builds, &config.jenkins_url, Local::now().timestamp());
+
+                    if due.is_empty() {
+                        println!("no builds to trigger");
+                        return
+                    }
+
+                    // Saved before triggering, so build isn't triggered twice if run is interrupted
+                    save_scheduled_builds(&pending).unwrap_or_else(|e| exit_with_error(e));
+
+                    let client = get_http_client();
+
+                    let duplicates = get_duplicate_build_policy(run_matches);
+
+                    let mut failed_builds = vec![];
+
+                    let mut job_list = None;
+
+                    for build in due {
+                        let job_name = match resolve_job_to_modify(&client, &config, &build.job_name, &mut job_list) {
+                            Ok(job_name) => job_name,
+                            Err(e) => {
+                                eprintln!("[{}] {e:#}", build.job_name);
+                                failed_builds.push(build);
+                                continue
+                            }
+                        };
+
+                        if !can_build_job(&config, &job_name) {
+                            eprintln!("[{job_name}] job doesn't have trigger token, username and token are required in config");
+                            failed_builds.push(build);
+                            continue
+                        }
+
+                        if duplicates == DuplicateBuildPolicy::Confirm {
+                            if let Some(outcome) = confirm_build(&client, &config.jenkins_url, config.get_username(),
+                                                                 config.get_token(), &job_name, &HashMap::new(),
+                                                                 &config.trigger_tokens) {
+                                if let Some(error) = outcome.error {
+                                    eprintln!("[{job_name}] unable to trigger build: {error}");
+                                    failed_builds.push(build);
+                                }
+                                continue
+                            }
+                        }
+
+                        match build_job(&client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                        &job_name, DuplicateBuildPolicy::Allow, None,
+                                        config.get_trigger_token(&job_name)) {
+                            Ok(BuildTrigger::Queued(_)) => {
+                                println!("[{job_name}] build queued (scheduled at {})", build.get_local_time());
+                                record_job_usage_of(&config, &job_name);
+                            }
+                            Ok(BuildTrigger::Skipped(_)) => println!("[{job_name}] build skipped"),
+                            Err(e) => {
+                                eprintln!("[{job_name}] unable to trigger build: {e}");
+                                failed_builds.push(build);
+                            }
+                        }
+                    }
+
+                    if !failed_builds.is_empty() {
+                        // Failed builds are put back, so they are retried on the next run
+                        if let Err(e) = add_scheduled_builds(&failed_builds) {
+                            eprintln!("error: unable to put failed builds back into schedule: {e:#}");
+                            failed_builds.iter().for_each(|b| eprintln!("{}  {}", b.get_local_time(), b.job_name));
+                        } else {
+                            failed_builds.iter().for_each(|b| eprintln!("[{}] build scheduled at {} is kept for the next run",
+                                                                        b.job_name, b.get_local_time()));
+                        }
+
+                        exit(EXIT_CODE);
+                    }
+                }
+                Some((SCHEDULER_LIST_COMMAND, _)) => builds.iter()
+                    .for_each(|b| println!("{}  {}  {}", b.get_local_time(), b.job_name, b.jenkins_url)),
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+}
+
+fn get_allow_duplicates_arg() -> Arg {
+    Arg::new(ALLOW_DUPLICATES_ARG)
+        .long(ALLOW_DUPLICATES_ARG)
+        .action(ArgAction::SetTrue)
+        .help("trigger build even if job has queued or running build with identical parameters, \
+               otherwise user is asked and build is skipped without terminal")
+}
+
+fn get_duplicate_build_policy(matches: &ArgMatches) -> DuplicateBuildPolicy {
+    match matches.get_flag(ALLOW_DUPLICATES_ARG) {
+        true => DuplicateBuildPolicy::Allow,
+        false => DuplicateBuildPolicy::Confirm
+    }
+}
+
+/// Duplicate builds of single job are confirmed before triggering, exits if build is declined.
+fn confirm_single_build(client: &Client, config: &AppConfig, job_name: &str,
+                        duplicates: DuplicateBuildPolicy) -> DuplicateBuildPolicy {
+    if duplicates != DuplicateBuildPolicy::Confirm {
+        return duplicates
+    }
+
+    if let Some(outcome) = confirm_build(client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                         job_name, &HashMap::new(), &config.trigger_tokens) {
+        match outcome.error {
+            Some(error) => {
+                eprintln!("error: {error}");
+                exit(EXIT_CODE);
+            }
+            None => exit(0)
+        }
+    }
+
+    DuplicateBuildPolicy::Allow
+}
+
+/// Triggered builds are recorded in local history, see `list --sort recent-use`.
+fn record_usage(config: &AppConfig, outcomes: &[BuildOutcome]) {
+    outcomes.iter()
+        .filter(|o| o.is_triggered())
+        .for_each(|o| record_job_usage_of(config, &o.job_name));
+}
+
+fn record_job_usage_of(config: &AppConfig, job_name: &str) {
+    if let Err(e) = record_job_usage(&config.jenkins_url, job_name) {
+        warn!("unable to record usage of job '{job_name}': {e}");
+    }
+}
+
+fn read_script_file(path: &str) -> String {
+    fs::read_to_string(path)
+        .unwrap_or_else(|e| exit_with_error(anyhow::Error::new(e).context(format!("unable to read file '{path}'"))))
+}
+
+/// Empty id is allowed for single pending input.
+fn find_input_action<'a>(actions: &'a [JenkinsInputAction], input_id: &str) -> &'a JenkinsInputAction {
+    let action = if input_id.is_empty() && actions.len() == 1 {
+        actions.first()
+
+    } else {
+        actions.iter().find(|a| a.id == input_id)
+    };
+
+    match action {
+        Some(action) => action,
+        None => {
+            eprintln!("pending input '{input_id}' not found, available inputs: {}",
+                      actions.iter().map(|a| a.id.as_str()).collect::<Vec<&str>>().join(", "));
+            exit(EXIT_CODE);
+        }
+    }
+}
+
+/// `--param NAME=VALUE` values.
+fn get_param_values(matches: &ArgMatches) -> HashMap<String, String> {
+    matches.get_many::<String>(PARAM_ARG)
+        .unwrap_or_default()
+        .map(|p| match p.split_once('=') {
+            Some((name, value)) => (name.to_string(), value.to_string()),
+            None => {
+                eprintln!("invalid parameter '{p}', expected format NAME=VALUE");
+                exit(EXIT_CODE);
+            }
+        })
+        .collect()
+}
+
+fn print_test_summary(client: &Client, config: &AppConfig, job_name: &str, number: u32) {
+    match get_test_report(client, &config.jenkins_url, config.get_username(), config.get_token(),
+                          job_name, &number.to_string()) {
+        Ok(Some(report)) => println!("{report}"),
+        Ok(None) => {}
+        Err(e) => eprintln!("unable to get test report: {e}")
+    }
+}
+
+/// `--status`, `--built-since` and `--not-built-since` arguments.
+fn get_job_status_filter(matches: &ArgMatches) -> JobStatusFilter {
+    JobStatusFilter {
+        statuses: matches.get_many::<String>(STATUS_ARG)
+            .unwrap_or_default()
+            .map(|s| match s.as_str() {
+                "failed" => JobStatus::Failed,
+                "unstable" => JobStatus::Unstable,
+                "success" => JobStatus::Success,
+                "disabled" => JobStatus::Disabled,
+                "running" => JobStatus::Running,
+                _ => JobStatus::NeverBuilt
+            })
+            .collect(),
+        built_since: matches.get_one::<Duration>(BUILT_SINCE_ARG).copied(),
+        not_built_since: matches.get_one::<Duration>(NOT_BUILT_SINCE_ARG).copied(),
+    }
+}
+
+/// `--retry`, `--retry-on`, `--retry-delay` and `--retry-backoff` arguments.
+fn get_retry_policy(matches: &ArgMatches) -> RetryPolicy {
+    RetryPolicy {
+        retries: *matches.get_one::<u32>(RETRY_ARG).unwrap(),
+        on: matches.get_one::<String>(RETRY_ON_ARG).unwrap()
+            .split(',')
+            .map(|r| r.trim().to_uppercase())
+            .filter(|r| !r.is_empty())
+            .collect(),
+        delay: *matches.get_one::<Duration>(RETRY_DELAY_ARG).unwrap(),
+        backoff: match matches.get_one::<String>(RETRY_BACKOFF_ARG).unwrap().as_str() {
+            "fixed" => RetryBackoff::Fixed,
+            _ => RetryBackoff::Exponential
+        },
+    }
+}
+
+fn load_config(matches: &ArgMatches) -> AppConfig {
+    let config = load_config_file(matches);
+
+    if !config.has_credentials() {
+        eprintln!("username and token are required in config");
+        exit(EXIT_CODE);
+    }
+
+    config
+}
+
+fn load_config_file(matches: &ArgMatches) -> AppConfig {
+    let config_file_path = get_config_path(matches);
+
+    if !config_file_path.exists() {
+        eprintln!("config file '{}' not found, create it from config.yml-dist", config_file_path.display());
+        exit(EXIT_CODE);
+    }
+
+    let mut config = load_config_from_file(&config_file_path)
+        .expect("unable to load config from file");
+
+    if matches.get_flag(REFRESH_ARG) {
+        config.cache.mode = CacheMode::Refresh;
+
+    } else if matches.get_flag(NO_CACHE_ARG) {
+        config.cache.mode = CacheMode::Bypass;
+    }
+
+    config
+}
+
+/// Credentials might be omitted in config if all built jobs have trigger tokens, see `can_build_job`.
+fn load_build_config(matches: &ArgMatches) -> AppConfig {
+    let config = load_config_file(matches);
+
+    if config.username.is_some() != config.token.is_some() {
+        eprintln!("both username and token should be set in config, or none of them to use trigger tokens only");
+        exit(EXIT_CODE);
+    }
+
+    config
+}
+
+fn can_build_job(config: &AppConfig, job_name: &str) -> bool {
+    config.has_credentials() || config.get_trigger_token(job_name).is_some()
+}
+
+fn get_config_path(matches: &ArgMatches) -> PathBuf {
+    let explicit_path = matches.get_one::<String>(CONFIG_ARG).cloned().or(env::var(CONFIG_ENV).ok());
+
+    get_config_file_path(explicit_path.as_deref())
+}
+
+fn get_http_client() -> Client {
+    ClientBuilder::new().build()
+        .expect("unable to build http client")
+}
+
+/// Full job name by url, alias, exact name or unique prefix or substring. Exits with candidates or suggestions otherwise.
+fn get_job_name(client: &Client, config: &AppConfig, name: &str) -> String {
+    match resolve_job(client, config, name, &mut None) {
+        Ok((job_name, _)) => job_name,
+        Err(e) => exit_with_resolve_error(e)
+    }
+}
+
+/// Job which is built or modified: name resolved from part of it should be confirmed by user,
+/// so exact name is required without terminal.
+fn get_job_name_to_modify(client: &Client, config: &AppConfig, name: &str) -> String {
+    resolve_job_to_modify(client, config, name, &mut None).unwrap_or_else(|e| exit_with_resolve_error(e))
+}
+
+fn resolve_job_to_modify(client: &Client, config: &AppConfig, name: &str,
+                         job_list: &mut Option<Vec<JenkinsJob>>) -> anyhow::Result<String> {
+    let (job_name, exact) = resolve_job(client, config, name, job_list)?;
+
+    if !exact && !confirm(&format!("job name '{name}' is resolved to '{job_name}', continue?")) {
+        return Err(anyhow!("job name '{name}' is resolved to '{job_name}', use exact job name"))
+    }
+
+    Ok(job_name)
+}
+
+/// Job name and `true` if it's exact (url, alias or existing job), `false` if it's resolved from part of name.
+/// Exact name is checked on server before matching since job list might be cached.
+///
+/// Job list is fetched on first use and kept in `job_list`, so it's fetched once for many names.
+fn resolve_job(client: &Client, config: &AppConfig, name: &str,
+               job_list: &mut Option<Vec<JenkinsJob>>) -> anyhow::Result<(String, bool)> {
+    if is_url(name) {
+        return Ok((get_jenkins_url(config, name).job_name, true))
+    }
+
+    let name = config.resolve_alias(name);
+
+    // Job list isn't available with trigger tokens only
+    if !config.has_credentials() {
+        return Ok((name, true))
+    }
+
+    let job_list = get_job_list_once(client, config, job_list)?;
+
+    if job_list.iter().any(|j| j.name == name) ||
+        is_job_exists(client, &config.jenkins_url, config.get_username(), config.get_token(), &name)? {
+        return Ok((name, true))
+    }
+
+    match resolve_job_name(&name, job_list) {
+        JobNameResolution::Found(job_name) => {
+            info!("job name '{name}' resolved to '{job_name}'");
+            Ok((job_name, false))
+        }
+        JobNameResolution::Ambiguous(candidates) =>
+            Err(anyhow!("job name '{name}' is ambiguous, candidates:\n  {}", candidates.join("\n  "))),
+        JobNameResolution::Unknown(suggestions) if suggestions.is_empty() =>
+            Err(anyhow!("job '{name}' not found")),
+        JobNameResolution::Unknown(suggestions) =>
+            Err(anyhow!("job '{name}' not found, did you mean:\n  {}", suggestions.join("\n  ")))
+    }
+}
+
+fn get_job_list_once<'a>(client: &Client, config: &AppConfig,
+                         job_list: &'a mut Option<Vec<JenkinsJob>>) -> anyhow::Result<&'a [JenkinsJob]> {
+    if job_list.is_none() {
+        *job_list = Some(get_jenkins_job_list(client, &config.jenkins_url, config.get_username(), config.get_token(),
+                                              &config.cache)?);
+    }
+
+    Ok(job_list.as_deref().unwrap_or_default())
+}
+
+fn exit_with_resolve_error(e: anyhow::Error) -> ! {
+    eprintln!("{e:#}");
+    exit(EXIT_CODE);
+}
+
+/// Job or build url should point to configured server.
+fn get_jenkins_url(config: &AppConfig, url: &str) -> JenkinsUrl {
+    let jenkins_url = parse_jenkins_url(url).unwrap_or_else(|e| exit_with_error(e));
+
+    if !jenkins_url.is_same_server(&config.jenkins_url) {
+        eprintln!("url '{url}' points to server '{}', configured server is '{}'",
+                  jenkins_url.server, config.jenkins_url);
+        exit(EXIT_CODE);
+    }
+
+    jenkins_url
+}
+
+/// Build argument or build from job url.
+/// Build argument, build of job url or `default` permalink.
+fn get_build(matches: &ArgMatches, default: Option<&str>) -> String {
+    let build = matches.get_one::<String>(BUILD_ARG).map(|b| b.as_str());
+    let job = matches.get_one::<String>(JOB_ARG).unwrap();
+
+    match select_build(build, job, default) {
+        Ok(build) => build,
+        Err(e) => {
+            eprintln!("{e}");
+            exit(EXIT_CODE);
+        }
+    }
+}
+
+/// Job names from `--name`, `--stdin` and `--mask` arguments, duplicates are removed.
+fn get_build_job_names(matches: &ArgMatches, client: &Client, config: &AppConfig) -> Vec<String> {
+    // Job list is fetched once for all names, e.g. with `--refresh`
+    let mut job_list = None;
+
+    let mut resolve = |name: &str| resolve_job_to_modify(client, config, name, &mut job_list)
+        .unwrap_or_else(|e| exit_with_resolve_error(e));
+
+    let mut job_names = matches.get_many::<String>(NAME_ARG)
+        .unwrap_or_default()
+        .map(|name| resolve(name))
+        .collect::<Vec<String>>();
+
+    if matches.get_flag(STDIN_ARG) {
+        io::stdin().lines()
+            .map(|line| line.expect("unable to read stdin"))
+            .map(|line| line.trim().to_string())
+            .filter(|line| !line.is_empty())
+            .for_each(|line| job_names.push(resolve(&line)));
+    }
+
+    if let Some(mask) = matches.get_one::<String>(MASK_ARG) {
+        let job_list = get_job_list_once(client, config, &mut job_list)
+            .unwrap_or_else(|e| exit_with_error(e));
+
+        filter_job_list(job_list.to_vec(), &config.list.exclude, Some(mask)).into_iter()
+            .for_each(|j| job_names.push(j.name));
+    }
+
+    let mut unique_job_names: Vec<String> = vec![];
+
+    job_names.into_iter()
+        .for_each(|j| if !unique_job_names.contains(&j) { unique_job_names.push(j) });
+
+    unique_job_names
+}
+
+fn exit_with_error(e: anyhow::Error) -> ! {
+    eprintln!("error: {}", e);
+    eprintln!("{}", e.root_cause());
+    exit(EXIT_CODE);
+}
+
+fn init_logging(logging_level: &str) -> anyhow::Result<()> {
+    let logging_config = get_logging_config(logging_level);
+    log4rs::init_config(logging_config).context("unable to init logging subsystem")?;
+    Ok(())
+}
+
+/// Relative paths in arguments (plan files, artifacts destination, etc.) are resolved from working directory.
+fn init_working_dir(matches: &ArgMatches) {
+    if let Some(working_directory) = matches.get_one::<String>(WORK_DIR_ARG) {
+        info!("working directory '{working_directory}'");
+
+        env::set_current_dir(working_directory).expect("couldn't set working directory");
+    }
 }
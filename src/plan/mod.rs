@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+use std::sync::mpsc;
+use std::thread;
+
+use anyhow::{anyhow, Context};
+use log::info;
+use reqwest::blocking::Client;
+use serde::Deserialize;
+
+use crate::config::TriggerToken;
+use crate::jenkins::bulk::{BuildOutcome, confirm_build, format_table, trigger_build};
+use crate::jenkins::duplicates::DuplicateBuildPolicy;
+use crate::jenkins::format_duration;
+
+const REFERENCE_START: &str = "${{";
+const REFERENCE_END: &str = "}}";
+
+/// Build chain from yaml file:
+///
+/// ```yaml
+/// steps:
+///   - id: build
+///     job: backend/api/build
+///     parameters:
+///       SCM_BRANCH: dev
+///
+///   - id: deploy
+///     job: backend/api/deploy-dev
+///     needs: [build]
+///     parameters:
+///       BUILD_NUMBER: ${{ steps.build.build_number }}
+///
+///   - id: notify
+///     job: tools/notify
+///     needs: [deploy]
+///     if: failure
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct BuildPlan {
+    pub steps: Vec<PlanStep>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "kebab-case")]
+pub struct PlanStep {
+    pub id: String,
+    pub job: String,
+    #[serde(default)]
+    pub parameters: HashMap<String, serde_yaml::Value>,
+    #[serde(default)]
+    pub needs: Vec<String>,
+    #[serde(default, rename = "if")]
+    pub condition: StepCondition,
+}
+
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum StepCondition {
+    /// All needed steps are successful
+    #[default]
+    Success,
+    /// Any of needed steps has failed
+    Failure,
+    Always,
+}
+
+pub struct PlanReport {
+    pub steps: Vec<(String, BuildOutcome)>,
+}
+
+impl PlanReport {
+    pub fn is_failed(&self) -> bool {
+        self.steps.iter().any(|(_, o)| o.is_failed())
+    }
+}
+
+pub fn load_plan_from_file(file_path: &Path) -> anyhow::Result<BuildPlan> {
+    info!("loading build plan from file '{}'", file_path.display());
+
+    let yaml = fs::read_to_string(file_path)
+        .context(format!("unable to read file '{}'", file_path.display()))?;
+
+    let plan = serde_yaml::from_str::<BuildPlan>(&yaml).context("unable to parse build plan")?;
+
+    validate_plan(&plan)?;
+
+    Ok(plan)
+}
+
+/// Step ids are unique, needed steps exist and there are no cycles.
+fn validate_plan(plan: &BuildPlan) -> anyhow::Result<()> {
+    for (index, step) in plan.steps.iter().enumerate() {
+        if plan.steps[..index].iter().any(|s| s.id == step.id) {
+            return Err(anyhow!("duplicate step id '{}'", step.id))
+        }
+
+        if let Some(unknown) = step.needs.iter().find(|n| !plan.steps.iter().any(|s| &s.id == *n)) {
+            return Err(anyhow!("step '{}' needs unknown step '{unknown}'", step.id))
+        }
+    }
+
+    let mut resolved: Vec<&str> = vec![];
+
+    while resolved.len() < plan.steps.len() {
+        let ready = plan.steps.iter()
+            .filter(|s| !resolved.contains(&s.id.as_str()))
+            .filter(|s| s.needs.iter().all(|n| resolved.contains(&n.as_str())))
+            .map(|s| s.id.as_str())
+            .collect::<Vec<&str>>();
+
+        if ready.is_empty() {
+            return Err(anyhow!("build plan has dependency cycle"))
+        }
+
+        resolved.extend(ready);
+    }
+
+    Ok(())
+}
+
+/// Steps are started as soon as their needed steps are finished, every build is awaited.
+/// Duplicate builds are confirmed on calling thread before step is started.
+pub fn run_plan(client: &Client, jenkins_url: &str, username: &str, token: &str,
+                plan: &BuildPlan, duplicates: DuplicateBuildPolicy, trigger_tokens: &[TriggerToken]) -> PlanReport {
+    let mut outcomes: HashMap<String, BuildOutcome> = HashMap::new();
+    let mut running: Vec<String> = vec![];
+
+    let (sender, receiver) = mpsc::channel::<(String, BuildOutcome)>();
+
+    while outcomes.len() < plan.steps.len() {
+        let ready = plan.steps.iter()
+            .filter(|s| !outcomes.contains_key(&s.id) && !running.contains(&s.id))
+            .filter(|s| s.needs.iter().all(|n| outcomes.contains_key(n)))
+            .collect::<Vec<&PlanStep>>();
+
+        for step in ready {
+            if !is_condition_met(step, &outcomes) {
+                println!("[{}] skipped", step.id);
+                outcomes.insert(step.id.to_string(), BuildOutcome::skipped(&step.job));
+                continue
+            }
+
+            let params = match resolve_parameters(step, &outcomes) {
+                Ok(params) => params,
+                Err(e) => {
+                    outcomes.insert(step.id.to_string(), BuildOutcome::from_error(&step.job, None, e));
+                    continue
+                }
+            };
+
+            if duplicates == DuplicateBuildPolicy::Confirm {
+                if let Some(outcome) = confirm_build(client, jenkins_url, username, token, &step.job, &params,
+                                                     trigger_tokens) {
+                    outcomes.insert(step.id.to_string(), outcome);
+                    continue
+                }
+            }
+
+            println!("[{}] starting job '{}'..", step.id, step.job);
+            running.push(step.id.to_string());
+
+            let sender = sender.clone();
+            let client = client.clone();
+            let jenkins_url = jenkins_url.to_string();
+            let username = username.to_string();
+            let token = token.to_string();
+            let step = step.clone();
+            let trigger_tokens = trigger_tokens.to_vec();
+
+            thread::spawn(move || {
+                let outcome = trigger_build(&client, &jenkins_url, &username, &token,
+                                            &step.job, &params, true, DuplicateBuildPolicy::Allow, None, None,
+                                            &trigger_tokens);
+                sender.send((step.id, outcome)).expect("unable to send step outcome");
+            });
+        }
+
+        if running.is_empty() {
+            continue
+        }
+
+        let (step_id, outcome) = receiver.recv().expect("unable to receive step outcome");
+
+        running.retain(|id| id != &step_id);
+        outcomes.insert(step_id, outcome);
+    }
+
+    PlanReport {
+        steps: plan.steps.iter()
+            .map(|s| (s.id.to_string(), outcomes.remove(&s.id).unwrap()))
+            .collect()
+    }
+}
+
+fn is_condition_met(step: &PlanStep, outcomes: &HashMap<String, BuildOutcome>) -> bool {
+    let needed = step.needs.iter().filter_map(|n| outcomes.get(n)).collect::<Vec<&BuildOutcome>>();
+
+    match step.condition {
+        StepCondition::Success => needed.iter().all(|o| o.result == "SUCCESS"),
+        StepCondition::Failure => needed.iter().any(|o| o.is_failed()),
+        StepCondition::Always => true,
+    }
+}
+
+fn resolve_parameters(step: &PlanStep, outcomes: &HashMap<String, BuildOutcome>) -> anyhow::Result<HashMap<String, String>> {
+    step.parameters.iter().map(|(name, value)| {
+        let value = match value {
+            serde_yaml::Value::String(value) => value.to_string(),
+            serde_yaml::Value::Bool(value) => value.to_string(),
+            serde_yaml::Value::Number(value) => value.to_string(),
+            serde_yaml::Value::Null => "".to_string(),
+            _ => return Err(anyhow!("parameter '{name}' of step '{}' should be scalar", step.id))
+        };
+
+        Ok((name.to_string(), resolve_references(&value, outcomes)?))
+    }).collect()
+}
+
+/// `${{ steps.<id>.build_number|result|job }}`
+fn resolve_references(value: &str, outcomes: &HashMap<String, BuildOutcome>) -> anyhow::Result<String> {
+    let mut result = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find(REFERENCE_START) {
+        let end = rest[start..].find(REFERENCE_END)
+            .ok_or(anyhow!("unclosed reference in '{value}'"))? + start;
+
+        let reference = rest[start + REFERENCE_START.len()..end].trim();
+
+        let parts = reference.split('.').collect::<Vec<&str>>();
+
+        let resolved = match parts.as_slice() {
+            ["steps", step_id, field] => {
+                let outcome = outcomes.get(*step_id)
+                    .ok_or(anyhow!("reference '{reference}' to step which isn't finished"))?;
+
+                match *field {
+                    "build_number" => outcome.number.map(|n| n.to_string())
+                        .ok_or(anyhow!("step '{step_id}' doesn't have build number"))?,
+                    "result" => outcome.result.to_string(),
+                    "job" => outcome.job_name.to_string(),
+                    _ => return Err(anyhow!("unknown field '{field}' in reference '{reference}'"))
+                }
+            }
+            _ => return Err(anyhow!("invalid reference '{reference}', expected steps.<id>.<field>"))
+        };
+
+        result.push_str(&rest[..start]);
+        result.push_str(&resolved);
+
+        rest = &rest[end + REFERENCE_END.len()..];
+    }
+
+    result.push_str(rest);
+
+    Ok(result)
+}
+
+impl Display for PlanReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut rows = vec![
+            ["STEP", "JOB", "BUILD", "RESULT", "DURATION", ""].map(|h| h.to_string()).to_vec()
+        ];
+
+        self.steps.iter().for_each(|(id, o)| rows.push(vec![
+            id.to_string(),
+            o.job_name.to_string(),
+            o.get_build_numbers(),
+            o.result.to_string(),
+            o.duration.map(format_duration).unwrap_or("-".to_string()),
+            o.error.clone().unwrap_or_default(),
+        ]));
+
+        write!(f, "{}", format_table(&rows))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::jenkins::bulk::BuildOutcome;
+    use crate::plan::{BuildPlan, is_condition_met, resolve_parameters, StepCondition, validate_plan};
+
+    const PLAN: &str = r#"
+steps:
+  - id: build
+    job: backend/api/build
+    parameters:
+      SCM_BRANCH: dev
+      CLEAR_CACHE: false
+
+  - id: deploy
+    job: backend/api/deploy-dev
+    needs: [build]
+    parameters:
+      BUILD_NUMBER: ${{ steps.build.build_number }}
+      DESCRIPTION: "build #${{ steps.build.build_number }} (${{ steps.build.result }})"
+
+  - id: notify
+    job: tools/notify
+    needs: [deploy]
+    if: failure
+"#;
+
+    #[test]
+    fn plan_should_be_parsed_and_validated() {
+        let plan = serde_yaml::from_str::<BuildPlan>(PLAN).unwrap();
+
+        assert_eq!(3, plan.steps.len());
+        assert_eq!(StepCondition::Success, plan.steps[0].condition);
+        assert_eq!(StepCondition::Failure, plan.steps[2].condition);
+
+        assert!(validate_plan(&plan).is_ok());
+    }
+
+    #[test]
+    fn cycles_and_unknown_steps_should_be_rejected() {
+        let mut plan = serde_yaml::from_str::<BuildPlan>(PLAN).unwrap();
+
+        plan.steps[0].needs = vec!["notify".to_string()];
+        assert!(validate_plan(&plan).is_err());
+
+        plan.steps[0].needs = vec!["unknown".to_string()];
+        assert!(validate_plan(&plan).is_err());
+    }
+
+    #[test]
+    fn references_should_be_resolved() {
+        let plan = serde_yaml::from_str::<BuildPlan>(PLAN).unwrap();
+
+        let outcomes = HashMap::from([("build".to_string(), get_outcome("backend/api/build", "SUCCESS"))]);
+
+        let build_params = resolve_parameters(&plan.steps[0], &outcomes).unwrap();
+        assert_eq!("false", build_params.get("CLEAR_CACHE").unwrap());
+
+        let deploy_params = resolve_parameters(&plan.steps[1], &outcomes).unwrap();
+        assert_eq!("120", deploy_params.get("BUILD_NUMBER").unwrap());
+        assert_eq!("build #120 (SUCCESS)", deploy_params.get("DESCRIPTION").unwrap());
+
+        assert!(resolve_parameters(&plan.steps[1], &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn conditions_should_be_checked() {
+        let plan = serde_yaml::from_str::<BuildPlan>(PLAN).unwrap();
+
+        let succeeded = HashMap::from([("deploy".to_string(), get_outcome("deploy", "SUCCESS"))]);
+        let failed = HashMap::from([("deploy".to_string(), get_outcome("deploy", "FAILURE"))]);
+
+        assert!(!is_condition_met(&plan.steps[2], &succeeded));
+        assert!(is_condition_met(&plan.steps[2], &failed));
+    }
+
+    fn get_outcome(job_name: &str, result: &str) -> BuildOutcome {
+        BuildOutcome {
+            job_name: job_name.to_string(),
+            number: Some(120),
+            result: result.to_string(),
+            duration: Some(1000),
+            error: None,
+            retried: vec![],
+        }
+    }
+}
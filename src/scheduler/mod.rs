@@ -0,0 +1,153 @@
+use std::fs;
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::dirs::get_state_dir;
+
+const SCHEDULE_DIR: &str = "schedule";
+const SCHEDULE_FILE: &str = "builds.json";
+
+const TIME_FORMATS: [&str; 2] = ["%Y-%m-%d %H:%M", "%Y-%m-%d %H:%M:%S"];
+
+/// Build recorded with `build --at`, triggered by `scheduler run` once time has come.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub struct ScheduledBuild {
+    pub job_name: String,
+    /// Server of config the build was scheduled with, empty for builds scheduled before it was stored
+    #[serde(default)]
+    pub jenkins_url: String,
+    /// Unix timestamp, seconds
+    pub at: i64,
+}
+
+impl ScheduledBuild {
+    /// Builds without server are triggered on any server.
+    pub fn is_for_server(&self, jenkins_url: &str) -> bool {
+        self.jenkins_url.is_empty() || self.jenkins_url.trim_end_matches('/') == jenkins_url.trim_end_matches('/')
+    }
+
+    pub fn get_local_time(&self) -> String {
+        Local.timestamp_opt(self.at, 0).single()
+            .map(|t| t.format(TIME_FORMATS[0]).to_string())
+            .unwrap_or(self.at.to_string())
+    }
+}
+
+/// `2026-10-19 02:00` in local timezone, should be after `now` (unix timestamp, seconds).
+pub fn parse_schedule_time(value: &str, now: i64) -> anyhow::Result<DateTime<Local>> {
+    let time = TIME_FORMATS.iter()
+        .find_map(|f| NaiveDateTime::parse_from_str(value.trim(), f).ok())
+        .ok_or(anyhow!("invalid time '{value}', expected format 'YYYY-MM-DD HH:MM'"))?;
+
+    let time = Local.from_local_datetime(&time).earliest()
+        .ok_or(anyhow!("time '{value}' doesn't exist in local timezone"))?;
+
+    if time.timestamp() <= now {
+        return Err(anyhow!("time '{value}' is in the past"))
+    }
+
+    Ok(time)
+}
+
+pub fn schedule_build(jenkins_url: &str, job_name: &str, at: &DateTime<Local>) -> anyhow::Result<()> {
+    add_scheduled_builds(&[
+        ScheduledBuild { job_name: job_name.to_string(), jenkins_url: jenkins_url.to_string(), at: at.timestamp() }
+    ])
+}
+
+/// Builds are added to the schedule as is, e.g. failed builds are put back for the next run.
+pub fn add_scheduled_builds(new_builds: &[ScheduledBuild]) -> anyhow::Result<()> {
+    let mut builds = load_scheduled_builds()?;
+
+    builds.extend_from_slice(new_builds);
+
+    save_scheduled_builds(&builds)
+}
+
+pub fn load_scheduled_builds() -> anyhow::Result<Vec<ScheduledBuild>> {
+    let file_path = get_state_dir().join(SCHEDULE_DIR).join(SCHEDULE_FILE);
+
+    if file_path.exists() {
+        let json = fs::read_to_string(&file_path)?;
+
+        let builds = serde_json::from_str(&json)
+            .context(format!("unable to parse scheduled builds from '{}'", file_path.display()))?;
+        debug!("scheduled builds loaded: {:?}", builds);
+
+        Ok(builds)
+
+    } else {
+        Ok(vec![])
+    }
+}
+
+pub fn save_scheduled_builds(builds: &[ScheduledBuild]) -> anyhow::Result<()> {
+    debug!("save scheduled builds: {:?}", builds);
+    let schedule_path = get_state_dir().join(SCHEDULE_DIR);
+
+    if !schedule_path.exists() {
+        fs::create_dir_all(&schedule_path)?;
+    }
+
+    let json = serde_json::to_string_pretty(&builds)?;
+
+    fs::write(schedule_path.join(SCHEDULE_FILE), json)?;
+
+    Ok(())
+}
+
+/// Builds of `jenkins_url` which time has come and builds still pending, including builds of other servers.
+pub fn split_due_builds(builds: Vec<ScheduledBuild>, jenkins_url: &str,
+                        now: i64) -> (Vec<ScheduledBuild>, Vec<ScheduledBuild>) {
+    builds.into_iter().partition(|b| {
+        let due = b.at <= now;
+
+        if due && !b.is_for_server(jenkins_url) {
+            warn!("build for job '{}' is scheduled on '{}', skipped for '{jenkins_url}'", b.job_name, b.jenkins_url);
+        }
+
+        due && b.is_for_server(jenkins_url)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Datelike, Timelike};
+
+    use crate::scheduler::{parse_schedule_time, ScheduledBuild, split_due_builds};
+
+    #[test]
+    fn schedule_time_should_be_parsed() {
+        let time = parse_schedule_time("2026-10-19 02:00", 0).unwrap();
+
+        assert_eq!((2026, 10, 19, 2, 0), (time.year(), time.month(), time.day(), time.hour(), time.minute()));
+
+        assert!(parse_schedule_time("tomorrow", 0).is_err());
+        assert!(parse_schedule_time("2026-10-19 02:00", time.timestamp()).is_err());
+    }
+
+    #[test]
+    fn due_builds_should_be_separated_from_pending() {
+        let get_build = |job_name: &str, jenkins_url: &str, at: i64| ScheduledBuild {
+            job_name: job_name.to_string(), jenkins_url: jenkins_url.to_string(), at
+        };
+
+        let builds = vec![
+            get_build("maintenance", "https://jenkins.company.com", 100),
+            get_build("cleanup", "https://jenkins.company.com", 300),
+            get_build("backup", "https://ci.company.com", 100),
+            get_build("legacy", "", 100),
+        ];
+
+        let (due, pending) = split_due_builds(builds, "https://jenkins.company.com/", 200);
+
+        let get_names = |builds: &[ScheduledBuild]| builds.iter().map(|b| b.job_name.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(vec!["maintenance", "legacy"], get_names(&due));
+        assert_eq!(vec!["cleanup", "backup"], get_names(&pending));
+    }
+}